@@ -1,75 +1,509 @@
+// Classes of the active tab and tab content, and of hidden tab contents. Replaced with the
+// `active_class` and `hidden_class` options when the file is installed or inlined.
+const ACTIVE_CLASS = 'active';
+const HIDDEN_CLASS = 'hidden';
+
+/**
+ * Read a value from local storage, if available.
+ *
+ * @param {string} key
+ * @returns {string | null}
+ */
+const getStorageItem = (key) => {
+    try {
+        return localStorage.getItem(key);
+    } catch {
+        return null;
+    }
+};
+
+/**
+ * Write a value to local storage, if available.
+ *
+ * @param {string} key
+ * @param {string} value
+ */
+const setStorageItem = (key, value) => {
+    try {
+        localStorage.setItem(key, value);
+    } catch {
+        // Local storage is unavailable, e.g. disabled by the browser.
+    }
+};
+
+/**
+ * Get the element containing the tab contents of tabs.
+ *
+ * @param {Element} container
+ * @returns {Element}
+ */
+const getTabPanels = (container) =>
+    Array.from(container.children).find(
+        (child) => child instanceof HTMLElement && 'tabpanels' in child.dataset
+    ) || container;
+
+/**
+ * Get the tablist of tabs.
+ *
+ * @param {Element} container
+ * @returns {Element | undefined}
+ */
+const getTablist = (container) =>
+    Array.from(container.children).find(
+        (child) => child instanceof HTMLElement && child.getAttribute('role') === 'tablist'
+    );
+
+/**
+ * Get the tabs of a tablist, including the tabs in the "More" menu.
+ *
+ * @param {Element} tablist
+ * @returns {HTMLElement[]}
+ */
+const getTabs = (tablist) =>
+    Array.from(tablist.querySelectorAll('[role="tab"]')).filter(
+        (tab) => tab instanceof HTMLElement
+    );
+
+/**
+ * Get the tabs containing a tab.
+ *
+ * @param {Element} tab
+ * @returns {Element | null}
+ */
+const getTabsContainer = (tab) => {
+    const tablist = tab.closest('[role="tablist"]');
+    return tablist && tablist.parentElement;
+};
+
+/**
+ * Open or close the "More" menu of a tablist.
+ *
+ * @param {Element} tablist
+ * @param {boolean} open
+ */
+const setMenuOpen = (tablist, open) => {
+    const button = tablist.querySelector('[data-tabmore]');
+    const menu = tablist.querySelector('[data-tabmenu]');
+    if (!(button instanceof HTMLElement) || !(menu instanceof HTMLElement)) {
+        return;
+    }
+
+    button.setAttribute('aria-expanded', String(open));
+    menu.hidden = !open;
+};
+
+/**
+ * Highlight code blocks and add copy buttons like mdBook's `book.js`, which only decorates the
+ * code blocks on the page when it is loaded.
+ *
+ * @param {Element} panel
+ */
+const decorateCodeBlocks = (panel) => {
+    for (const block of panel.querySelectorAll('pre code')) {
+        if (!block.classList.contains('hljs')) {
+            if (window.hljs) {
+                window.hljs.highlightBlock(block);
+            }
+            block.classList.add('hljs');
+        }
+
+        const pre = block.parentElement;
+        if (!window.playground_copyable || !pre || pre.querySelector('.clip-button')) {
+            continue;
+        }
+
+        let buttons = pre.querySelector('.buttons');
+        if (!buttons) {
+            buttons = document.createElement('div');
+            buttons.className = 'buttons';
+            pre.insertBefore(buttons, pre.firstChild);
+        }
+
+        const clipButton = document.createElement('button');
+        clipButton.className = 'fa fa-copy clip-button';
+        clipButton.title = 'Copy to clipboard';
+        clipButton.setAttribute('aria-label', clipButton.title);
+        clipButton.innerHTML = '<i class="tooltiptext"></i>';
+        // mdBook only resets the tooltip of the copy buttons on the page when it is loaded.
+        clipButton.addEventListener('mouseout', () => {
+            if (clipButton.firstElementChild instanceof HTMLElement) {
+                clipButton.firstElementChild.innerText = '';
+            }
+            clipButton.className = 'fa fa-copy clip-button';
+        });
+        buttons.insertBefore(clipButton, buttons.firstChild);
+    }
+};
+
+/**
+ * Instantiate the content of a tab from its template, if it is not rendered yet.
+ *
+ * @param {Element} container
+ * @param {string | null} id
+ */
+const renderTabContent = (container, id) => {
+    for (const child of getTabPanels(container).children) {
+        if (child instanceof HTMLTemplateElement && child.dataset.tabcontent === id) {
+            const panel = child.content.firstElementChild;
+            child.replaceWith(child.content);
+            if (panel) {
+                decorateCodeBlocks(panel);
+            }
+            return;
+        }
+    }
+};
+
+/**
+ * Replace the `<details>` elements rendered for readers without JS with tab panels.
+ *
+ * @param {Element} container
+ */
+const enhanceFallback = (container) => {
+    const tablist = getTablist(container);
+    const tabs = tablist ? getTabs(tablist) : [];
+
+    let active;
+    for (const details of Array.from(getTabPanels(container).children)) {
+        if (!(details instanceof HTMLDetailsElement)) {
+            continue;
+        }
+
+        const panel = document.createElement('div');
+        for (const attribute of details.attributes) {
+            if (attribute.name !== 'open') {
+                panel.setAttribute(attribute.name, attribute.value);
+            }
+        }
+
+        const tab = tabs.find(
+            (tab) => tab.getAttribute('aria-controls') === details.id
+        );
+        panel.setAttribute('role', 'tabpanel');
+        if (tab) {
+            panel.setAttribute('aria-labelledby', tab.id);
+            if (tab.getAttribute('aria-selected') === 'true') {
+                active = tab;
+            }
+        }
+
+        for (const child of Array.from(details.childNodes)) {
+            if (!(child instanceof HTMLElement && child.tagName === 'SUMMARY')) {
+                panel.appendChild(child);
+            }
+        }
+
+        details.replaceWith(panel);
+    }
+
+    delete container.dataset.tabfallback;
+
+    if (active instanceof HTMLElement && active.dataset.tabname) {
+        changeTab(container, active.dataset.tabname, active.id);
+    }
+};
+
 /**
  * Change active tab of tabs.
  *
+ * The tab is selected by ID if given, otherwise the first tab with the name is selected.
+ *
  * @param {Element} container
  * @param {string} name
+ * @param {string} [id]
  */
-const changeTab = (container, name) => {
-    for (const child of container.children) {
-        if (!(child instanceof HTMLElement)) {
-            continue;
+const changeTab = (container, name, id) => {
+    const tablist = getTablist(container);
+    if (!tablist) {
+        return;
+    }
+
+    const tabs = getTabs(tablist);
+    const target = tabs.find((tab) => (id ? tab.id === id : tab.dataset.tabname === name));
+    if (!target) {
+        return;
+    }
+
+    renderTabContent(container, target.getAttribute('aria-controls'));
+
+    for (const tab of tabs) {
+        if (tab === target) {
+            tab.classList.add(ACTIVE_CLASS);
+            tab.setAttribute('aria-selected', 'true');
+            tab.setAttribute('tabindex', '0');
+        } else {
+            tab.classList.remove(ACTIVE_CLASS);
+            tab.setAttribute('aria-selected', 'false');
+            tab.setAttribute('tabindex', '-1');
         }
+    }
 
-        if (child.classList.contains('mdbook-tabs')) {
-            for (const tab of child.children) {
-                if (!(tab instanceof HTMLElement)) {
-                    continue;
-                }
+    const badge = getTabPanels(container).querySelector(':scope > [data-tabbadge]');
+    if (badge instanceof HTMLElement) {
+        badge.textContent = (target.textContent || '').trim();
+    }
 
-                if (tab.dataset.tabname === name) {
-                    tab.classList.add('active');
-                } else {
-                    tab.classList.remove('active');
-                }
+    // The "More" button is marked as active while the selected tab is in its menu.
+    const more = tablist.querySelector('[data-tabmore]');
+    if (more) {
+        more.classList.toggle(ACTIVE_CLASS, target.closest('[data-tabmenu]') !== null);
+    }
+
+    for (const child of getTabPanels(container).children) {
+        if (child instanceof HTMLElement && child.getAttribute('role') === 'tabpanel') {
+            const active = child.getAttribute('aria-labelledby') === target.id;
+            child.classList.toggle(ACTIVE_CLASS, active);
+            if (HIDDEN_CLASS) {
+                child.classList.toggle(HIDDEN_CLASS, !active);
             }
-        } else if (child.classList.contains('mdbook-tab-content')) {
-            if (child.dataset.tabname === name) {
-                child.classList.remove('hidden');
-            } else {
-                child.classList.add('hidden');
+        }
+    }
+};
+
+/**
+ * Check if tabs contain a tab.
+ *
+ * @param {Element} container
+ * @param {string} name
+ * @returns {boolean}
+ */
+const hasTab = (container, name) => {
+    const tablist = getTablist(container);
+
+    return tablist ? getTabs(tablist).some((tab) => tab.dataset.tabname === name) : false;
+};
+
+/**
+ * Move focus between tabs using the keyboard, following the WAI-ARIA tabs pattern. Vertical tabs
+ * use the up and down arrow keys instead of left and right. Tabs in the "More" menu come after the
+ * visible tabs, and the menu is opened while one of them has focus.
+ *
+ * @param {KeyboardEvent} event
+ */
+const handleTabKeydown = (event) => {
+    const tab = event.currentTarget;
+    const tablist = tab instanceof HTMLElement && tab.closest('[role="tablist"]');
+    if (!(tab instanceof HTMLElement) || !tablist) {
+        return;
+    }
+
+    const siblings = getTabs(tablist);
+    const index = siblings.indexOf(tab);
+    const vertical = tablist.getAttribute('aria-orientation') === 'vertical';
+
+    let target;
+    switch (event.key) {
+        case 'Escape': {
+            const more = tablist.querySelector('[data-tabmore]');
+            if (tab.closest('[data-tabmenu]') && more instanceof HTMLElement) {
+                event.preventDefault();
+                setMenuOpen(tablist, false);
+                more.focus();
             }
+            return;
+        }
+        case vertical ? 'ArrowUp' : 'ArrowLeft':
+            target = siblings[(index - 1 + siblings.length) % siblings.length];
+            break;
+        case vertical ? 'ArrowDown' : 'ArrowRight':
+            target = siblings[(index + 1) % siblings.length];
+            break;
+        case 'Home':
+            target = siblings[0];
+            break;
+        case 'End':
+            target = siblings[siblings.length - 1];
+            break;
+        default:
+            return;
+    }
+
+    event.preventDefault();
+
+    if (target instanceof HTMLElement) {
+        setMenuOpen(tablist, target.closest('[data-tabmenu]') !== null);
+        target.focus();
+        target.click();
+    }
+};
+
+/**
+ * Toggle the "More" menu and move focus into it using the keyboard. The menu is closed when focus
+ * or a click leaves it.
+ *
+ * @param {Element} button
+ */
+const initMoreButton = (button) => {
+    const tablist = button.closest('[role="tablist"]');
+    const more = button.parentElement;
+    if (!tablist || !more) {
+        return;
+    }
+
+    button.addEventListener('click', () => {
+        setMenuOpen(tablist, button.getAttribute('aria-expanded') !== 'true');
+    });
+
+    button.addEventListener('keydown', (event) => {
+        if (!(event instanceof KeyboardEvent) || !['ArrowDown', 'Enter', ' '].includes(event.key)) {
+            return;
         }
+
+        const menu = tablist.querySelector('[data-tabmenu]');
+        const tabs = menu ? getTabs(menu) : [];
+        const target = tabs.find((tab) => tab.getAttribute('aria-selected') === 'true') || tabs[0];
+        if (target) {
+            event.preventDefault();
+            setMenuOpen(tablist, true);
+            target.focus();
+        }
+    });
+
+    more.addEventListener('focusout', (event) => {
+        if (!(event.relatedTarget instanceof Node) || !more.contains(event.relatedTarget)) {
+            setMenuOpen(tablist, false);
+        }
+    });
+
+    document.addEventListener('click', (event) => {
+        if (event.target instanceof Node && !more.contains(event.target)) {
+            setMenuOpen(tablist, false);
+        }
+    });
+};
+
+/**
+ * Change active tab of tabs to the tab or tab content referenced by the location hash.
+ */
+const changeTabFromHash = () => {
+    if (!location.hash) {
+        return;
+    }
+
+    let id;
+    try {
+        id = decodeURIComponent(location.hash.slice(1));
+    } catch {
+        return;
+    }
+
+    const template = document.querySelector(`template[data-tabcontent="${CSS.escape(id)}"]`);
+    if (template && template.parentElement) {
+        renderTabContent(template.parentElement.parentElement || template.parentElement, id);
+    }
+
+    const element = document.getElementById(id);
+    if (!(element instanceof HTMLElement)) {
+        return;
+    }
+
+    let container;
+    let tabId;
+    if (element.getAttribute('role') === 'tab') {
+        container = getTabsContainer(element);
+        tabId = element.id;
+    } else if (element.getAttribute('role') === 'tabpanel') {
+        container = element.parentElement && element.parentElement.parentElement;
+        tabId = element.getAttribute('aria-labelledby');
+    }
+
+    if (container && element.dataset.tabname) {
+        changeTab(container, element.dataset.tabname, tabId);
+        element.scrollIntoView();
     }
 };
 
 document.addEventListener('DOMContentLoaded', () => {
-    const tabs = document.querySelectorAll('.mdbook-tab');
+    for (const container of document.querySelectorAll('[data-tabfallback]')) {
+        enhanceFallback(container);
+    }
+
+    for (const button of document.querySelectorAll('[data-tabmore]')) {
+        initMoreButton(button);
+    }
+
+    const tabs = document.querySelectorAll('[role="tab"][data-tabname]');
     for (const tab of tabs) {
-        tab.addEventListener('click', () => {
+        tab.addEventListener('keydown', handleTabKeydown);
+        tab.addEventListener('click', (event) => {
             if (!(tab instanceof HTMLElement)) {
                 return;
             }
 
-            if (!tab.parentElement || !tab.parentElement.parentElement) {
+            const container = getTabsContainer(tab);
+            if (!container) {
                 return;
             }
 
-            const container = tab.parentElement.parentElement;
+            // The menu stays open when tabs are selected with the keyboard, see `handleTabKeydown`.
+            const tablist = tab.closest('[role="tablist"]');
+            if (tablist && event.detail > 0 && tab.closest('[data-tabmenu]')) {
+                setMenuOpen(tablist, false);
+            }
+
             const name = tab.dataset.tabname;
             const global = container.dataset.tabglobal;
+            const remember = container.dataset.tabremember;
+            const sync = container.dataset.tabsync;
 
-            changeTab(container, name);
+            changeTab(container, name, tab.id);
+
+            if ('tabhash' in container.dataset && tab.id) {
+                history.replaceState(null, '', `#${tab.id}`);
+            }
+
+            if (sync) {
+                const syncContainers = document.querySelectorAll('[data-tabsync]');
+                for (const syncContainer of syncContainers) {
+                    if (
+                        syncContainer !== container &&
+                        syncContainer instanceof HTMLElement &&
+                        syncContainer.dataset.tabsync === sync
+                    ) {
+                        changeTab(syncContainer, name);
+                    }
+                }
+            }
+
+            if (remember) {
+                setStorageItem(`mdbook-tabs-remember-${remember}`, name);
+            }
 
             if (global) {
-                localStorage.setItem(`mdbook-tabs-${global}`, name);
+                setStorageItem(`mdbook-tabs-${global}`, name);
 
                 const globalContainers = document.querySelectorAll(
-                    `.mdbook-tabs-container[data-tabglobal="${global}"]`
+                    `[data-tabglobal="${CSS.escape(global)}"]`
                 );
                 for (const globalContainer of globalContainers) {
-                    changeTab(globalContainer, name);
+                    if (globalContainer !== container) {
+                        changeTab(globalContainer, name);
+                    }
                 }
             }
         });
     }
 
-    const containers = document.querySelectorAll('.mdbook-tabs-container[data-tabglobal]');
+    const rememberContainers = document.querySelectorAll('[data-tabremember]');
+    for (const container of rememberContainers) {
+        const remember = container.dataset.tabremember;
+
+        const name = getStorageItem(`mdbook-tabs-remember-${remember}`);
+        if (name && hasTab(container, name)) {
+            changeTab(container, name);
+        }
+    }
+
+    const containers = document.querySelectorAll('[data-tabglobal]');
     for (const container of containers) {
         const global = container.dataset.tabglobal;
 
-        const name = localStorage.getItem(`mdbook-tabs-${global}`);
+        const name = getStorageItem(`mdbook-tabs-${global}`);
         if (name) {
             changeTab(container, name);
         }
     }
+
+    changeTabFromHash();
+    window.addEventListener('hashchange', changeTabFromHash);
 });
@@ -0,0 +1,141 @@
+use mdbook_plugin_utils::{html::escape, markdown::parse_marker};
+use pulldown_cmark::Event;
+
+use crate::config::PreprocessorConfig;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum AdmonitionKind {
+    Note,
+    Tip,
+    Warning,
+    Danger,
+}
+
+impl AdmonitionKind {
+    pub const ALL: [AdmonitionKind; 4] = [
+        AdmonitionKind::Note,
+        AdmonitionKind::Tip,
+        AdmonitionKind::Warning,
+        AdmonitionKind::Danger,
+    ];
+
+    pub fn keyword(&self) -> &'static str {
+        match self {
+            AdmonitionKind::Note => "note",
+            AdmonitionKind::Tip => "tip",
+            AdmonitionKind::Warning => "warning",
+            AdmonitionKind::Danger => "danger",
+        }
+    }
+
+    pub fn title(&self) -> &'static str {
+        match self {
+            AdmonitionKind::Note => "Note",
+            AdmonitionKind::Tip => "Tip",
+            AdmonitionKind::Warning => "Warning",
+            AdmonitionKind::Danger => "Danger",
+        }
+    }
+
+    pub fn icon(&self) -> &'static str {
+        match self {
+            AdmonitionKind::Note => "ℹ️",
+            AdmonitionKind::Tip => "💡",
+            AdmonitionKind::Warning => "⚠️",
+            AdmonitionKind::Danger => "⛔",
+        }
+    }
+
+    pub fn is_start(&self, event: &Event) -> bool {
+        marker(event).is_some_and(|(name, _)| name == self.keyword())
+    }
+
+    pub fn is_end(&self, event: &Event) -> bool {
+        marker(event).is_some_and(|(name, _)| name.strip_prefix("end") == Some(self.keyword()))
+    }
+}
+
+// Markers never span multiple lines, which excludes the text of code blocks.
+pub fn marker_text<'e>(event: &'e Event) -> Option<&'e str> {
+    match event {
+        Event::Text(text) => Some(text.as_ref()),
+        Event::Html(html) => Some(html.trim_end()),
+        _ => None,
+    }
+    .filter(|text| !text.contains('\n'))
+}
+
+// Name and attributes of a marker, e.g. `{{# note title="Careful" }}`.
+pub fn marker<'e>(event: &'e Event) -> Option<(&'e str, &'e str)> {
+    marker_text(event).and_then(parse_marker)
+}
+
+pub fn admonition(
+    kind: AdmonitionKind,
+    title: Option<&str>,
+    content: &str,
+    config: &PreprocessorConfig,
+) -> String {
+    format!(
+        "<div class=\"{} {}\" role=\"note\">\n<p class=\"{}\"><span class=\"{}\" aria-hidden=\"true\">{}</span>{}</p>\n\n{}\n\n</div>",
+        config.class("admonition"),
+        config.class(&format!("admonition-{}", kind.keyword())),
+        config.class("admonition-title"),
+        config.class("admonition-icon"),
+        kind.icon(),
+        escape(title.unwrap_or(kind.title())),
+        content
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use pulldown_cmark::CowStr;
+    use test_log::test;
+
+    use super::*;
+
+    #[test]
+    fn test_markers() {
+        let kind = AdmonitionKind::Warning;
+
+        assert!(kind.is_start(&Event::Text(CowStr::from("{{#warning}}"))));
+        assert!(kind.is_start(&Event::Text(CowStr::from(
+            "{{#warning title=\"Careful\" }}"
+        ))));
+        assert!(!kind.is_start(&Event::Text(CowStr::from("{{#warnings}}"))));
+        assert!(!kind.is_start(&Event::Text(CowStr::from("{{#note}}"))));
+        assert!(!kind.is_start(&Event::Text(CowStr::from(
+            "{{#warning }}\nContent.\n{{#endwarning }}\n"
+        ))));
+        assert!(kind.is_start(&Event::Text(CowStr::from(
+            "{{# warning\ttitle=\"Careful\"}}"
+        ))));
+        assert!(!kind.is_start(&Event::Text(CowStr::from("{{#warning }} Content."))));
+        assert!(kind.is_end(&Event::Text(CowStr::from("{{#endwarning }}"))));
+        assert!(kind.is_end(&Event::Text(CowStr::from("{{# endwarning}}"))));
+        assert!(!kind.is_end(&Event::Text(CowStr::from("{{#endnote}}"))));
+        assert!(!kind.is_end(&Event::Text(CowStr::from("{{#warning}}"))));
+    }
+
+    #[test]
+    fn test_admonition() {
+        let config = PreprocessorConfig {
+            class_prefix: "my-".to_string(),
+        };
+
+        assert_eq!(
+            "<div class=\"my-admonition my-admonition-tip\" role=\"note\">\n\
+            <p class=\"my-admonition-title\"><span class=\"my-admonition-icon\" aria-hidden=\"true\">💡</span>Tip</p>\n\
+            \n\
+            Content.\n\
+            \n\
+            </div>",
+            admonition(AdmonitionKind::Tip, None, "Content.", &config)
+        );
+        assert!(
+            admonition(AdmonitionKind::Danger, Some("<Stop>"), "", &config)
+                .contains("</span>&lt;Stop&gt;</p>")
+        );
+    }
+}
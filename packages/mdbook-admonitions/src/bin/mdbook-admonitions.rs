@@ -0,0 +1,40 @@
+use std::{env, fs};
+
+use anyhow::Result;
+use clap::{command, Subcommand};
+use mdbook::MDBook;
+use mdbook_admonitions::{css, AdmonitionsPreprocessor, PreprocessorConfig};
+use mdbook_plugin_utils::preprocessor::run_preprocessor_cli;
+
+#[derive(Subcommand)]
+enum Commands {
+    Install,
+}
+
+fn main() -> Result<()> {
+    let mut logger = env_logger::builder();
+    if env::var("RUST_LOG").is_err() {
+        logger.filter_module("mdbook_admonitions", log::LevelFilter::Info);
+    }
+    logger.init();
+
+    let preprocessor = AdmonitionsPreprocessor::new();
+
+    run_preprocessor_cli(command!(), &preprocessor, |command| match command {
+        Commands::Install => handle_install(),
+    })
+}
+
+fn handle_install() -> Result<()> {
+    let book = MDBook::load(env::current_dir()?)?;
+    let config = PreprocessorConfig::from_config(&book.config)?;
+    let directory = book.root.join("theme");
+
+    if !directory.exists() {
+        fs::create_dir(&directory)?;
+    }
+
+    fs::write(directory.join("admonitions.css"), css(&config))?;
+
+    Ok(())
+}
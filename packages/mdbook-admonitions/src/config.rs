@@ -0,0 +1,42 @@
+use anyhow::{bail, Result};
+use mdbook::Config;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(default)]
+pub struct PreprocessorConfig {
+    pub class_prefix: String,
+}
+
+impl Default for PreprocessorConfig {
+    fn default() -> Self {
+        Self {
+            class_prefix: "mdbook-".to_string(),
+        }
+    }
+}
+
+impl PreprocessorConfig {
+    pub fn from_config(book_config: &Config) -> Result<Self> {
+        let config: Self = book_config
+            .get_deserialized_opt("preprocessor.admonitions")?
+            .unwrap_or_default();
+
+        if !config
+            .class_prefix
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+        {
+            bail!(
+                "Admonitions class prefix `{}` is invalid, it should only contain ASCII letters, digits, `-` and `_`.",
+                config.class_prefix
+            );
+        }
+
+        Ok(config)
+    }
+
+    pub fn class(&self, name: &str) -> String {
+        format!("{}{}", self.class_prefix, name)
+    }
+}
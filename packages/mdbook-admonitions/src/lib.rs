@@ -0,0 +1,9 @@
+mod admonition;
+mod config;
+mod preprocessor;
+mod theme;
+
+pub use admonition::AdmonitionKind;
+pub use config::PreprocessorConfig;
+pub use preprocessor::AdmonitionsPreprocessor;
+pub use theme::css;
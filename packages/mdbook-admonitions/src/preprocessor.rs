@@ -0,0 +1,186 @@
+use anyhow::{bail, Error, Result};
+use mdbook::{
+    book::{Book, Chapter},
+    preprocess::{Preprocessor, PreprocessorContext},
+    BookItem,
+};
+use mdbook_plugin_utils::markdown::{parse_blocks_multi, position, BlockMatcher, MarkerAttributes};
+use pulldown_cmark::Event;
+
+use crate::{
+    admonition::{admonition, marker, AdmonitionKind},
+    config::PreprocessorConfig,
+};
+
+pub struct AdmonitionsPreprocessor;
+
+impl AdmonitionsPreprocessor {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl Default for AdmonitionsPreprocessor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Preprocessor for AdmonitionsPreprocessor {
+    fn name(&self) -> &str {
+        "admonitions"
+    }
+
+    fn run(&self, ctx: &PreprocessorContext, book: Book) -> Result<Book> {
+        let config = PreprocessorConfig::from_config(&ctx.config)?;
+        let mut book = book.clone();
+
+        process_items(&mut book.sections, &config)?;
+
+        Ok(book)
+    }
+
+    fn supports_renderer(&self, _renderer: &str) -> bool {
+        true
+    }
+}
+
+fn process_items(items: &mut Vec<BookItem>, config: &PreprocessorConfig) -> Result<()> {
+    for section in items {
+        if let BookItem::Chapter(chapter) = section {
+            chapter.content = process_content(&chapter_path(chapter), &chapter.content, config)?;
+
+            process_items(&mut chapter.sub_items, config)?;
+        }
+    }
+
+    Ok(())
+}
+
+fn chapter_path(chapter: &Chapter) -> String {
+    chapter
+        .source_path
+        .as_ref()
+        .map(|path| path.display().to_string())
+        .unwrap_or(chapter.name.clone())
+}
+
+fn location(path: &str, source: &str, offset: usize) -> String {
+    let position = position(source, offset);
+
+    format!("{}:{}:{}", path, position.line, position.column)
+}
+
+fn process_content(path: &str, content: &str, config: &PreprocessorConfig) -> Result<String> {
+    let starts = AdmonitionKind::ALL.map(|kind| move |event: &Event| kind.is_start(event));
+    let ends = AdmonitionKind::ALL.map(|kind| move |event: &Event| kind.is_end(event));
+    let matchers = starts
+        .iter()
+        .zip(ends.iter())
+        .map(|(is_start, is_end)| -> BlockMatcher { (is_start, is_end) })
+        .collect::<Vec<_>>();
+
+    let blocks = parse_blocks_multi(content, &matchers).map_err(|error| {
+        let location = location(path, content, error.span().start);
+        Error::new(error).context(format!("Failed to parse admonitions at {}.", location))
+    })?;
+
+    let mut processed = String::with_capacity(content.len());
+    let mut last = 0;
+
+    for (index, block) in blocks {
+        let kind = AdmonitionKind::ALL[index];
+
+        if !block.closed {
+            bail!(
+                "Admonition `{}` at {} is not closed.",
+                kind.keyword(),
+                location(path, content, block.span.start)
+            );
+        }
+
+        let (name, attributes) = marker(&block.start_event().0).unwrap_or_default();
+        let marker = MarkerAttributes::from_attributes(name, attributes).map_err(|error| {
+            let location = location(path, content, block.span.start);
+            Error::new(error).context(format!("Failed to parse admonition at {}.", location))
+        })?;
+
+        processed.push_str(&content[last..block.span.start]);
+        processed.push_str(&admonition(
+            kind,
+            marker.get("title"),
+            block.content(content)?,
+            config,
+        ));
+        last = block.span.end;
+    }
+
+    processed.push_str(&content[last..]);
+
+    Ok(processed)
+}
+
+#[cfg(test)]
+mod test {
+    use test_log::test;
+
+    use super::*;
+
+    #[test]
+    fn test_process_content() -> Result<()> {
+        let content = "\
+        Before.\n\
+        \n\
+        {{#note }}\n\
+        Some **note**.\n\
+        {{#endnote }}\n\
+        \n\
+        Between.\n\
+        \n\
+        {{#danger title=\"Watch out\" }}\n\
+        \n\
+        ```markdown\n\
+        {{#enddanger }}\n\
+        ```\n\
+        \n\
+        {{#enddanger }}\n\
+        \n\
+        After.";
+
+        let actual = process_content("test.md", content, &PreprocessorConfig::default())?;
+
+        assert!(actual
+            .starts_with("Before.\n\n<div class=\"mdbook-admonition mdbook-admonition-note\""));
+        assert!(actual.contains("Note</p>\n\n\nSome **note**.\n\n\n</div>\n\nBetween."));
+        assert!(actual.contains("<div class=\"mdbook-admonition mdbook-admonition-danger\""));
+        assert!(actual.contains("Watch out</p>"));
+        assert!(actual.contains("```markdown\n{{#enddanger }}\n```"));
+        assert!(actual.ends_with("</div>\n\nAfter."));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_process_content_marker_whitespace() -> Result<()> {
+        let content = "{{# tip\ttitle=\"Hint\"}}\nTip.\n{{# endtip }}\n";
+
+        let actual = process_content("test.md", content, &PreprocessorConfig::default())?;
+
+        assert!(actual.starts_with("<div class=\"mdbook-admonition mdbook-admonition-tip\""));
+        assert!(actual.contains("Hint</p>\n\n\nTip.\n\n\n</div>"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_process_content_not_closed() {
+        let content = "Before.\n\n{{#tip }}\nTip.";
+
+        let actual = process_content("test.md", content, &PreprocessorConfig::default());
+
+        assert_eq!(
+            "Admonition `tip` at test.md:3:1 is not closed.",
+            actual.unwrap_err().to_string()
+        );
+    }
+}
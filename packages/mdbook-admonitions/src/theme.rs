@@ -0,0 +1,7 @@
+use crate::config::PreprocessorConfig;
+
+const CSS: &str = include_str!("theme/admonitions.css");
+
+pub fn css(config: &PreprocessorConfig) -> String {
+    CSS.replace(".mdbook-", &format!(".{}", config.class_prefix))
+}
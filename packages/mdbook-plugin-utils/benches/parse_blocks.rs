@@ -0,0 +1,97 @@
+// Counts the allocations and measures the time of parsing blocks in a large document, compared to
+// only parsing the markdown events. Run with `cargo bench -p mdbook-plugin-utils`.
+
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    hint::black_box,
+    sync::atomic::{AtomicUsize, Ordering},
+    time::Instant,
+};
+
+use mdbook_plugin_utils::markdown::{count_blocks, parse_blocks, parse_nested_blocks};
+use pulldown_cmark::{Event, Parser};
+
+struct CountingAllocator;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+const ITERATIONS: usize = 20;
+
+// Most text borrows from the content, but inline code over multiple lines and link destinations
+// with escapes are owned, so cloning their events allocates.
+fn document(nested: bool) -> String {
+    let tab = "\
+        {{#tab }}\n\
+        Some *text* with a [link](https://example.com/some\\_page) and `inline code\n\
+        over two lines`.\n\
+        \n\
+        ```rust\n\
+        let a = 1 + 2;\n\
+        ```\n\
+        {{#endtab }}\n\
+        \n\
+        Text between the blocks.\n\
+        \n";
+
+    match nested {
+        true => format!("{{{{#tab }}}}\n{}{{{{#endtab }}}}\n\n", tab.repeat(10)).repeat(200),
+        false => tab.repeat(2000),
+    }
+}
+
+fn is_start(event: &Event) -> bool {
+    matches!(event, Event::Text(text) if text.starts_with("{{#tab "))
+}
+
+fn is_end(event: &Event) -> bool {
+    matches!(event, Event::Text(text) if text.starts_with("{{#endtab "))
+}
+
+fn bench<T>(name: &str, f: impl Fn() -> T) {
+    black_box(f());
+
+    let allocations = ALLOCATIONS.load(Ordering::Relaxed);
+    let start = Instant::now();
+    for _ in 0..ITERATIONS {
+        black_box(f());
+    }
+    let elapsed = start.elapsed() / ITERATIONS as u32;
+    let allocations = (ALLOCATIONS.load(Ordering::Relaxed) - allocations) / ITERATIONS;
+
+    println!("{name:<24} {allocations:>10} allocations {elapsed:>12.2?}");
+}
+
+fn main() {
+    let content = document(false);
+    println!("Document of {} bytes.", content.len());
+
+    bench("events", || {
+        Parser::new(&content).into_offset_iter().count()
+    });
+    bench("count_blocks", || count_blocks(&content, is_start, is_end));
+    bench("parse_blocks", || parse_blocks(&content, is_start, is_end));
+
+    let content = document(true);
+    println!("Nested document of {} bytes.", content.len());
+
+    bench("events", || {
+        Parser::new(&content).into_offset_iter().count()
+    });
+    bench("parse_nested_blocks", || {
+        parse_nested_blocks(&content, is_start, is_end, 2)
+    });
+}
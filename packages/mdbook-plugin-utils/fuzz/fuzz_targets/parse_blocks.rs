@@ -0,0 +1,76 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use mdbook_plugin_utils::markdown::{parse_nested_blocks, Block};
+use pulldown_cmark::{Event, Tag, TagEnd};
+
+fn is_char_boundary(content: &str, index: usize) -> bool {
+    index <= content.len() && content.is_char_boundary(index)
+}
+
+fn check_block(content: &str, block: &Block) {
+    let span = &block.span;
+    assert!(span.start <= span.end);
+    assert!(is_char_boundary(content, span.start) && is_char_boundary(content, span.end));
+
+    if let Some(inner_span) = &block.inner_span {
+        assert!(inner_span.start < inner_span.end);
+        assert!(span.start <= inner_span.start && inner_span.end <= span.end);
+        assert!(
+            is_char_boundary(content, inner_span.start)
+                && is_char_boundary(content, inner_span.end)
+        );
+    }
+
+    assert!(block.content(content).is_ok());
+    assert!(block.outer(content).is_ok());
+
+    for child in &block.children {
+        check_block(content, child);
+    }
+}
+
+// The first byte selects the start and end of blocks and the maximum depth, the rest is the content.
+fuzz_target!(|data: &[u8]| {
+    let Some((&selector, data)) = data.split_first() else {
+        return;
+    };
+    let Ok(content) = std::str::from_utf8(data) else {
+        return;
+    };
+
+    let max_depth = (selector >> 2) as usize % 4 + 1;
+
+    let result = match selector % 4 {
+        0 => parse_nested_blocks(
+            content,
+            |event| matches!(event, Event::Text(text) if text.starts_with("{{#tab ")),
+            |event| matches!(event, Event::Text(text) if text.starts_with("{{#endtab ")),
+            max_depth,
+        ),
+        1 => parse_nested_blocks(
+            content,
+            |event| matches!(event, Event::Start(Tag::Emphasis)),
+            |event| matches!(event, Event::End(TagEnd::Emphasis)),
+            max_depth,
+        ),
+        2 => parse_nested_blocks(
+            content,
+            |event| matches!(event, Event::Text(text) if text.trim() == ":::tabs"),
+            |event| matches!(event, Event::Text(text) if text.trim() == ":::"),
+            max_depth,
+        ),
+        _ => parse_nested_blocks(
+            content,
+            |event| matches!(event, Event::Start(Tag::BlockQuote(_))),
+            |event| matches!(event, Event::End(TagEnd::BlockQuote)),
+            max_depth,
+        ),
+    };
+
+    if let Ok(blocks) = result {
+        for block in &blocks {
+            check_block(content, block);
+        }
+    }
+});
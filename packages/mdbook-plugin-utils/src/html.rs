@@ -0,0 +1,33 @@
+// Escapes text for HTML content and quoted attribute values.
+pub fn escape(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+
+    for c in value.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(c),
+        }
+    }
+
+    escaped
+}
+
+#[cfg(test)]
+mod test {
+    use test_log::test;
+
+    use super::*;
+
+    #[test]
+    fn test_escape() {
+        assert_eq!("Linux", escape("Linux"));
+        assert_eq!(
+            "&lt;b&gt;Say &quot;hi&quot; &amp; &#39;bye&#39;&lt;/b&gt;",
+            escape("<b>Say \"hi\" & 'bye'</b>")
+        );
+    }
+}
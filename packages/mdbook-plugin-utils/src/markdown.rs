@@ -1,5 +1,13 @@
 mod block;
 mod code_block;
+mod error;
+mod events;
+mod location;
+mod marker;
 
 pub use block::*;
 pub use code_block::*;
+pub use error::*;
+pub use events::*;
+pub use location::*;
+pub use marker::*;
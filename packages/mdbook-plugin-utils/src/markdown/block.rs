@@ -1,137 +1,727 @@
 use std::ops::Range;
 
-use anyhow::{bail, Result};
 use log::debug;
-use pulldown_cmark::{Event, Parser};
+use pulldown_cmark::{DefaultBrokenLinkCallback, Event, OffsetIter, Parser, Tag, TagEnd};
+
+use crate::markdown::{
+    error::BlockParseError,
+    location::{location, Location},
+    marker::split_marker,
+};
 
 #[derive(Clone, Debug, PartialEq)]
 pub struct Block<'a> {
     pub closed: bool,
+    pub implicitly_closed: bool,
     pub events: Vec<(Event<'a>, Range<usize>)>,
     pub span: Range<usize>,
-    pub inner_span: Range<usize>,
+    pub inner_span: Option<Range<usize>>,
+    pub depth: usize,
+    pub children: Vec<Block<'a>>,
 }
 
 impl<'a> Block<'a> {
     pub fn new(first_event: Event<'a>, first_span: Range<usize>) -> Self {
+        Self::new_nested(first_event, first_span, 0)
+    }
+
+    pub fn new_nested(first_event: Event<'a>, first_span: Range<usize>, depth: usize) -> Self {
         let span = first_span.clone();
-        let inner_span = 0..0;
 
         Block {
             closed: false,
+            implicitly_closed: false,
             events: vec![(first_event, first_span)],
             span,
-            inner_span,
+            inner_span: None,
+            depth,
+            children: vec![],
+        }
+    }
+
+    pub fn content<'s>(&self, source: &'s str) -> Result<&'s str, BlockParseError> {
+        match &self.inner_span {
+            Some(inner_span) => slice(source, inner_span),
+            None => Ok(""),
+        }
+    }
+
+    // Blocks without inner span (e.g. not closed) or with only start and end events are empty.
+    pub fn is_empty_inner(&self) -> bool {
+        self.inner_span.as_ref().is_none_or(Range::is_empty)
+    }
+
+    pub fn outer<'s>(&self, source: &'s str) -> Result<&'s str, BlockParseError> {
+        slice(source, &self.span)
+    }
+
+    pub fn raw_events<'s>(
+        &self,
+        source: &'s str,
+    ) -> Result<Vec<(&Event<'a>, &'s str)>, BlockParseError> {
+        self.events
+            .iter()
+            .map(|(event, span)| Ok((event, slice(source, span)?)))
+            .collect()
+    }
+
+    pub fn start_event(&self) -> &(Event<'a>, Range<usize>) {
+        &self.events[0]
+    }
+
+    // Blocks that are not closed or closed implicitly by the next block have no end event.
+    pub fn end_event(&self) -> Option<&(Event<'a>, Range<usize>)> {
+        match self.closed && !self.implicitly_closed {
+            true => self.events.last(),
+            false => None,
+        }
+    }
+
+    pub fn location(&self, source: &str) -> Location {
+        location(source, &self.span)
+    }
+
+    // Line of the start event, so markers split into multiple text events by escapes are complete.
+    fn start_marker<'s>(&self, source: &'s str) -> Option<&'s str> {
+        let line = source.get(self.start_event().1.start..)?;
+
+        Some(line.split_once('\n').map_or(line, |(line, _)| line))
+    }
+
+    // Name of the `{{#name attributes}}` marker starting the block, e.g. `tabs`. Blocks started by
+    // other events have no marker name.
+    pub fn extract_marker_name<'s>(&self, source: &'s str) -> Option<&'s str> {
+        split_marker(self.start_marker(source)?)
+            .ok()
+            .map(|(name, _)| name)
+    }
+
+    // Attributes of the start marker as written, e.g. `global="example"`, which can be parsed with
+    // `MarkerAttributes`. Markers without attributes have an empty attribute string.
+    pub fn extract_marker_attributes<'s>(&self, source: &'s str) -> Option<&'s str> {
+        split_marker(self.start_marker(source)?)
+            .ok()
+            .map(|(_, attributes)| attributes)
+    }
+
+    fn close_implicitly(&mut self, boundary: usize) {
+        self.closed = true;
+        self.implicitly_closed = true;
+
+        let first = self.events[0].1.clone();
+        self.events
+            .retain(|(_, span)| *span == first || span.end <= boundary);
+
+        let end = self
+            .events
+            .iter()
+            .map(|(_, span)| span.end)
+            .max()
+            .unwrap_or(self.span.end);
+        let start = self.events[0].1.end.min(end);
+
+        self.span = self.span.start..end;
+        self.inner_span = non_empty(start..end);
+    }
+
+    fn close(&mut self, event: Event<'a>, span: Range<usize>) {
+        self.closed = true;
+        self.span = self.span.start..span.end;
+        self.events.push((event, span));
+
+        let block_span = self.span.clone();
+        let mut seen_first = false;
+        self.events.retain(|(_, span)| {
+            if !seen_first {
+                seen_first = true;
+                true
+            } else if span.start == block_span.start && span.end != block_span.end {
+                false
+            } else {
+                span.start >= block_span.start && span.end <= block_span.end
+            }
+        });
+
+        // Blocks with only start and end events have no inner span.
+        if self.events.len() > 2 {
+            let first = &self.events[1].1;
+            let last = &self.events[self.events.len() - 2].1;
+            let mut inner_span = first.start..last.end;
+
+            let start_span = &self.events[0].1;
+            if *start_span != block_span {
+                inner_span.start = inner_span.start.max(start_span.end);
+            }
+
+            let end_span = &self.events[self.events.len() - 1].1;
+            if *end_span != block_span {
+                inner_span.end = inner_span.end.min(end_span.start);
+            }
+            inner_span.end = inner_span.end.max(inner_span.start);
+
+            self.inner_span = non_empty(inner_span);
         }
     }
 }
 
+// Empty inner spans, e.g. of zero-length events, are normalized to `None`.
+fn non_empty(span: Range<usize>) -> Option<Range<usize>> {
+    debug_assert!(span.start <= span.end, "Invalid span {:?}.", span);
+
+    (span.start < span.end).then_some(span)
+}
+
+fn slice<'s>(source: &'s str, span: &Range<usize>) -> Result<&'s str, BlockParseError> {
+    source
+        .get(span.clone())
+        .ok_or_else(|| BlockParseError::SpanOutOfBounds {
+            span: span.clone(),
+            len: source.len(),
+        })
+}
+
 pub fn parse_blocks<IsStartFn, IsEndFn>(
     content: &str,
     is_start: IsStartFn,
     is_end: IsEndFn,
-) -> Result<Vec<Block>>
+) -> Result<Vec<Block<'_>>, BlockParseError>
 where
     IsStartFn: Fn(&Event) -> bool,
     IsEndFn: Fn(&Event) -> bool,
 {
-    let mut blocks: Vec<Block> = vec![];
+    parse_nested_blocks(content, is_start, is_end, 1)
+}
+
+pub fn parse_blocks_strict<IsStartFn, IsEndFn>(
+    content: &str,
+    is_start: IsStartFn,
+    is_end: IsEndFn,
+) -> Result<Vec<Block<'_>>, BlockParseError>
+where
+    IsStartFn: Fn(&Event) -> bool,
+    IsEndFn: Fn(&Event) -> bool,
+{
+    let blocks = parse_blocks(content, is_start, is_end)?;
+
+    if let Some(block) = blocks.iter().find(|block| !block.closed) {
+        return Err(BlockParseError::UnclosedBlock {
+            span: block.span.clone(),
+        });
+    }
+
+    Ok(blocks)
+}
+
+pub fn parse_nested_blocks<IsStartFn, IsEndFn>(
+    content: &str,
+    is_start: IsStartFn,
+    is_end: IsEndFn,
+    max_depth: usize,
+) -> Result<Vec<Block<'_>>, BlockParseError>
+where
+    IsStartFn: Fn(&Event) -> bool,
+    IsEndFn: Fn(&Event) -> bool,
+{
+    parse_nested_blocks_with_parser(
+        Parser::new(content).into_offset_iter(),
+        is_start,
+        is_end,
+        max_depth,
+    )
+}
+
+pub fn parse_blocks_with_parser<'a, Events, IsStartFn, IsEndFn>(
+    events: Events,
+    is_start: IsStartFn,
+    is_end: IsEndFn,
+) -> Result<Vec<Block<'a>>, BlockParseError>
+where
+    Events: IntoIterator<Item = (Event<'a>, Range<usize>)>,
+    IsStartFn: Fn(&Event) -> bool,
+    IsEndFn: Fn(&Event) -> bool,
+{
+    parse_nested_blocks_with_parser(events, is_start, is_end, 1)
+}
+
+pub fn parse_nested_blocks_with_parser<'a, Events, IsStartFn, IsEndFn>(
+    events: Events,
+    is_start: IsStartFn,
+    is_end: IsEndFn,
+    max_depth: usize,
+) -> Result<Vec<Block<'a>>, BlockParseError>
+where
+    Events: IntoIterator<Item = (Event<'a>, Range<usize>)>,
+    IsStartFn: Fn(&Event) -> bool,
+    IsEndFn: Fn(&Event) -> bool,
+{
+    BlocksIter::new(events, is_start, is_end, max_depth).collect()
+}
+
+// Parses the blocks in a range of the content, with spans relative to the whole content. The range
+// is parsed as a separate document, so it should start and end outside of containers, e.g. code
+// blocks. A block that starts in the range but is not closed before its end is an error.
+pub fn parse_blocks_in_range<IsStartFn, IsEndFn>(
+    content: &str,
+    range: Range<usize>,
+    is_start: IsStartFn,
+    is_end: IsEndFn,
+) -> Result<Vec<Block<'_>>, BlockParseError>
+where
+    IsStartFn: Fn(&Event) -> bool,
+    IsEndFn: Fn(&Event) -> bool,
+{
+    let offset = range.start;
+    let events = Parser::new(slice(content, &range)?)
+        .into_offset_iter()
+        .map(|(event, span)| (event, (span.start + offset)..(span.end + offset)));
+    let blocks = parse_blocks_with_parser(events, is_start, is_end)?;
+
+    if let Some(block) = blocks.iter().find(|block| !block.closed) {
+        return Err(BlockParseError::UnclosedBlock {
+            span: block.span.clone(),
+        });
+    }
+
+    Ok(blocks)
+}
+
+pub fn iter_blocks<IsStartFn, IsEndFn>(
+    content: &str,
+    is_start: IsStartFn,
+    is_end: IsEndFn,
+) -> BlocksIter<'_, OffsetIter<'_, DefaultBrokenLinkCallback>, IsStartFn, IsEndFn>
+where
+    IsStartFn: Fn(&Event) -> bool,
+    IsEndFn: Fn(&Event) -> bool,
+{
+    BlocksIter::new(Parser::new(content).into_offset_iter(), is_start, is_end, 1)
+}
+
+// Tracks whether `event` is inside a code block and returns whether it can be a marker. Text inside
+// code blocks is never a marker, so that markers can be documented in code.
+fn marker_event(code_block: &mut bool, event: &Event) -> bool {
+    match event {
+        Event::Start(Tag::CodeBlock(_)) => {
+            *code_block = true;
+            true
+        }
+        Event::End(TagEnd::CodeBlock) => {
+            *code_block = false;
+            true
+        }
+        _ => !*code_block,
+    }
+}
+
+// Counts the blocks `parse_blocks` would return, without collecting their events.
+pub fn count_blocks<IsStartFn, IsEndFn>(
+    content: &str,
+    is_start: IsStartFn,
+    is_end: IsEndFn,
+) -> Result<usize, BlockParseError>
+where
+    IsStartFn: Fn(&Event) -> bool,
+    IsEndFn: Fn(&Event) -> bool,
+{
+    let mut count = 0;
+    let mut open: Option<Range<usize>> = None;
+    let mut code_block = false;
 
     for (event, span) in Parser::new(content).into_offset_iter() {
-        debug!("{:?} {:?}", event, span);
+        let marker = marker_event(&mut code_block, &event);
 
-        if is_start(&event) {
-            if let Some(block) = blocks.last_mut() {
-                if !block.closed {
-                    bail!("Block is not closed. Nested blocks are not supported.");
-                }
+        if marker && is_start(&event) {
+            if let Some(parent_span) = open {
+                return Err(BlockParseError::UnexpectedNestedBlock { span, parent_span });
             }
 
-            blocks.push(Block::new(event, span));
-        } else if is_end(&event) {
-            if let Some(block) = blocks.last_mut() {
-                if !block.closed {
-                    block.closed = true;
-                    block.span = block.span.start..span.end;
-                    block.events.push((event, span));
+            count += 1;
+            open = Some(span);
+        } else if marker && is_end(&event) {
+            open = None;
+        }
+    }
 
-                    let mut seen_first = false;
-                    block.events.retain(|(_, span)| {
-                        if !seen_first {
-                            seen_first = true;
-                            true
-                        } else if span.start == block.span.start && span.end != block.span.end {
-                            false
-                        } else {
-                            span.start >= block.span.start && span.end <= block.span.end
-                        }
-                    });
-
-                    if let (Some((_, first)), Some((_, last))) = (
-                        block.events.get(1),
-                        block.events.get(block.events.len() - 2),
-                    ) {
-                        block.inner_span = first.start..last.end;
+    Ok(count)
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct BlockLimits {
+    pub max_blocks: Option<usize>,
+    pub max_block_size: Option<usize>,
+}
+
+fn check_limits(
+    limits: &BlockLimits,
+    stack: &[Block],
+    count: usize,
+    start: bool,
+    span: &Range<usize>,
+) -> Result<(), BlockParseError> {
+    if let Some(max) = limits.max_blocks {
+        if start && count >= max {
+            return Err(BlockParseError::TooManyBlocks {
+                span: span.clone(),
+                max,
+            });
+        }
+    }
+
+    if let (Some(max), Some(block)) = (limits.max_block_size, stack.first()) {
+        if span.end.saturating_sub(block.span.start) > max {
+            return Err(BlockParseError::BlockTooLarge {
+                span: block.span.start..span.end,
+                max,
+            });
+        }
+    }
+
+    Ok(())
+}
+
+pub struct BlocksIter<'a, Events, IsStartFn, IsEndFn>
+where
+    Events: Iterator<Item = (Event<'a>, Range<usize>)>,
+{
+    events: Events,
+    is_start: IsStartFn,
+    is_end: IsEndFn,
+    max_depth: usize,
+    limits: BlockLimits,
+    count: usize,
+    stack: Vec<Block<'a>>,
+    code_block: bool,
+    done: bool,
+}
+
+impl<'a, Events, IsStartFn, IsEndFn> BlocksIter<'a, Events, IsStartFn, IsEndFn>
+where
+    Events: Iterator<Item = (Event<'a>, Range<usize>)>,
+    IsStartFn: Fn(&Event) -> bool,
+    IsEndFn: Fn(&Event) -> bool,
+{
+    pub fn new<IntoEvents>(
+        events: IntoEvents,
+        is_start: IsStartFn,
+        is_end: IsEndFn,
+        max_depth: usize,
+    ) -> Self
+    where
+        IntoEvents: IntoIterator<IntoIter = Events>,
+    {
+        BlocksIter {
+            events: events.into_iter(),
+            is_start,
+            is_end,
+            max_depth,
+            limits: BlockLimits::default(),
+            count: 0,
+            stack: vec![],
+            code_block: false,
+            done: false,
+        }
+    }
+
+    pub fn with_limits(mut self, limits: BlockLimits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    fn finish(&mut self) -> Option<Block<'a>> {
+        while let Some(block) = self.stack.pop() {
+            match self.stack.last_mut() {
+                Some(parent) => parent.children.push(block),
+                None => return Some(block),
+            }
+        }
+
+        None
+    }
+}
+
+impl<'a, Events, IsStartFn, IsEndFn> Iterator for BlocksIter<'a, Events, IsStartFn, IsEndFn>
+where
+    Events: Iterator<Item = (Event<'a>, Range<usize>)>,
+    IsStartFn: Fn(&Event) -> bool,
+    IsEndFn: Fn(&Event) -> bool,
+{
+    type Item = Result<Block<'a>, BlockParseError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+
+        for (event, span) in self.events.by_ref() {
+            debug!("{:?} {:?}", event, span);
+
+            let marker = marker_event(&mut self.code_block, &event);
+
+            let start = marker && (self.is_start)(&event);
+
+            if let Err(error) = check_limits(&self.limits, &self.stack, self.count, start, &span) {
+                self.done = true;
+                return Some(Err(error));
+            }
+
+            if start {
+                if self.stack.len() >= self.max_depth {
+                    self.done = true;
+
+                    return Some(Err(BlockParseError::UnexpectedNestedBlock {
+                        span,
+                        parent_span: self.stack.last().map_or(0..0, |block| block.span.clone()),
+                    }));
+                }
+
+                for block in &mut self.stack {
+                    block.events.push((event.clone(), span.clone()));
+                }
+
+                let depth = self.stack.len();
+                self.count += 1;
+                self.stack.push(Block::new_nested(event, span, depth));
+            } else if marker && (self.is_end)(&event) {
+                if let Some(mut block) = self.stack.pop() {
+                    for parent in &mut self.stack {
+                        parent.events.push((event.clone(), span.clone()));
+                    }
+
+                    block.close(event, span);
+
+                    match self.stack.last_mut() {
+                        Some(parent) => parent.children.push(block),
+                        None => return Some(Ok(block)),
                     }
                 }
-            }
-        } else if let Some(block) = blocks.last_mut() {
-            if !block.closed && span.start >= block.span.start {
-                block.events.push((event, span));
+            } else if let Some((block, parents)) = self.stack.split_last_mut() {
+                // Only outer blocks get a copy, so events are not cloned when blocks are not nested.
+                // Borrowed text is cheap to clone, but text with escapes or entities is owned.
+                for parent in parents {
+                    if span.start >= parent.span.start {
+                        parent.events.push((event.clone(), span.clone()));
+                    }
+                }
+
+                if span.start >= block.span.start {
+                    block.events.push((event, span));
+                }
             }
         }
+
+        self.done = true;
+
+        self.finish().map(Ok)
     }
+}
 
-    Ok(blocks)
+pub fn parse_blocks_with_gaps<IsStartFn, IsEndFn>(
+    content: &str,
+    is_start: IsStartFn,
+    is_end: IsEndFn,
+) -> Result<(Vec<Block<'_>>, Vec<Range<usize>>), BlockParseError>
+where
+    IsStartFn: Fn(&Event) -> bool,
+    IsEndFn: Fn(&Event) -> bool,
+{
+    let blocks = parse_blocks(content, is_start, is_end)?;
+    let gaps = gaps(content, &blocks);
+
+    Ok((blocks, gaps))
 }
 
-#[cfg(test)]
-mod test {
-    use pulldown_cmark::{CodeBlockKind, CowStr, Tag, TagEnd};
-    use test_log::test;
+pub fn gaps(content: &str, blocks: &[Block]) -> Vec<Range<usize>> {
+    let mut gaps: Vec<Range<usize>> = vec![];
+    let mut start: usize = 0;
 
-    use super::*;
+    for block in blocks {
+        if block.span.start > start {
+            gaps.push(start..block.span.start);
+        }
 
-    #[test]
-    fn test_parse_blocks() -> Result<()> {
-        let content = "\
-        ```toml\n\
-        key1 = \"value1\"\n\
-        key2 = \"value2\"\n\
-        ```";
-        let expected: Vec<Block> = vec![Block {
-            closed: true,
-            events: vec![
-                (
-                    Event::Start(Tag::CodeBlock(CodeBlockKind::Fenced(CowStr::from("toml")))),
-                    0..43,
-                ),
-                (
-                    Event::Text(CowStr::from("key1 = \"value1\"\nkey2 = \"value2\"\n")),
-                    8..40,
-                ),
-                (Event::End(TagEnd::CodeBlock), 0..43),
-            ],
-            span: 0..43,
-            inner_span: 8..40,
-        }];
+        start = start.max(block.span.end);
+    }
 
-        let actual = parse_blocks(
-            content,
-            |event| matches!(event, Event::Start(Tag::CodeBlock(CodeBlockKind::Fenced(tag))) if tag == &CowStr::from("toml")),
-            |event| matches!(event, Event::End(TagEnd::CodeBlock)),
-        )?;
+    if content.len() > start {
+        gaps.push(start..content.len());
+    }
 
-        assert_eq!(expected, actual);
+    gaps
+}
 
-        Ok(())
+pub type BlockMatcher<'m> = (&'m dyn Fn(&Event) -> bool, &'m dyn Fn(&Event) -> bool);
+
+pub fn parse_blocks_multi<'c>(
+    content: &'c str,
+    matchers: &[BlockMatcher],
+) -> Result<Vec<(usize, Block<'c>)>, BlockParseError> {
+    let mut blocks: Vec<(usize, Block)> = vec![];
+    let mut code_block = false;
+
+    for (event, span) in Parser::new(content).into_offset_iter() {
+        debug!("{:?} {:?}", event, span);
+
+        let marker = marker_event(&mut code_block, &event);
+
+        let open = blocks
+            .last_mut()
+            .filter(|(_, block)| !block.closed)
+            .map(|(index, block)| (*index, block));
+
+        let start = matchers
+            .iter()
+            .position(|(is_start, _)| is_start(&event))
+            .filter(|_| marker);
+
+        if let Some(index) = start {
+            if let Some((_, block)) = open {
+                return Err(BlockParseError::UnexpectedNestedBlock {
+                    span,
+                    parent_span: block.span.clone(),
+                });
+            }
+
+            blocks.push((index, Block::new(event, span)));
+        } else if let Some((index, block)) = open {
+            let (_, is_end) = matchers[index];
+
+            if marker && is_end(&event) {
+                block.close(event, span);
+            } else if span.start >= block.span.start {
+                block.events.push((event, span));
+            }
+        }
     }
 
-    #[test]
-    fn test_parse_blocks_surrounded() -> Result<()> {
-        let content = "\
-        Some text before the code block.\n\
-        \n\
+    Ok(blocks)
+}
+
+// A start event while a block is open closes that block, instead of being a nested block.
+pub fn parse_blocks_lenient<IsStartFn, IsEndFn>(
+    content: &str,
+    is_start: IsStartFn,
+    is_end: IsEndFn,
+) -> Vec<Block<'_>>
+where
+    IsStartFn: Fn(&Event) -> bool,
+    IsEndFn: Fn(&Event) -> bool,
+{
+    let mut blocks: Vec<Block> = vec![];
+    let mut code_block = false;
+
+    for (event, span) in Parser::new(content).into_offset_iter() {
+        debug!("{:?} {:?}", event, span);
+
+        let marker = marker_event(&mut code_block, &event);
+
+        let open = blocks.last_mut().filter(|block| !block.closed);
+
+        if marker && is_start(&event) {
+            if let Some(block) = open {
+                block.close_implicitly(span.start);
+            }
+
+            blocks.push(Block::new(event, span));
+        } else if let Some(block) = open {
+            if marker && is_end(&event) {
+                block.close(event, span);
+            } else if span.start >= block.span.start {
+                block.events.push((event, span));
+            }
+        }
+    }
+
+    blocks
+}
+
+// A single delimiter opens and closes blocks, e.g. a repeated fence. The first delimiter opens a
+// block and the next one closes it, so an odd number of delimiters leaves the last block unclosed.
+pub fn parse_toggle_blocks<IsDelimiterFn>(
+    content: &str,
+    is_delimiter: IsDelimiterFn,
+) -> Vec<Block<'_>>
+where
+    IsDelimiterFn: Fn(&Event) -> bool,
+{
+    let mut blocks: Vec<Block> = vec![];
+    let mut code_block = false;
+
+    for (event, span) in Parser::new(content).into_offset_iter() {
+        debug!("{:?} {:?}", event, span);
+
+        let marker = marker_event(&mut code_block, &event);
+
+        let open = blocks.last_mut().filter(|block| !block.closed);
+
+        if marker && is_delimiter(&event) {
+            match open {
+                Some(block) => block.close(event, span),
+                None => blocks.push(Block::new(event, span)),
+            }
+        } else if let Some(block) = open {
+            if span.start >= block.span.start {
+                block.events.push((event, span));
+            }
+        }
+    }
+
+    blocks
+}
+
+#[cfg(test)]
+mod test {
+    use std::cell::Cell;
+
+    use anyhow::Result;
+    use pulldown_cmark::{CodeBlockKind, CowStr, Options};
+    use test_log::test;
+
+    use super::*;
+    use crate::markdown::{parse_code_blocks, MarkerAttributes, Position};
+
+    #[test]
+    fn test_parse_blocks() -> Result<()> {
+        let content = "\
+        ```toml\n\
+        key1 = \"value1\"\n\
+        key2 = \"value2\"\n\
+        ```";
+        let expected: Vec<Block> = vec![Block {
+            closed: true,
+            implicitly_closed: false,
+            events: vec![
+                (
+                    Event::Start(Tag::CodeBlock(CodeBlockKind::Fenced(CowStr::from("toml")))),
+                    0..43,
+                ),
+                (
+                    Event::Text(CowStr::from("key1 = \"value1\"\nkey2 = \"value2\"\n")),
+                    8..40,
+                ),
+                (Event::End(TagEnd::CodeBlock), 0..43),
+            ],
+            span: 0..43,
+            inner_span: Some(8..40),
+            depth: 0,
+            children: vec![],
+        }];
+
+        let actual = parse_blocks(
+            content,
+            |event| matches!(event, Event::Start(Tag::CodeBlock(CodeBlockKind::Fenced(tag))) if tag == &CowStr::from("toml")),
+            |event| matches!(event, Event::End(TagEnd::CodeBlock)),
+        )?;
+
+        assert_eq!(expected, actual);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_blocks_surrounded() -> Result<()> {
+        let content = "\
+        Some text before the code block.\n\
+        \n\
         ```toml\n\
         key1 = \"value1\"\n\
         key2 = \"value2\"\n\
@@ -140,6 +730,7 @@ mod test {
         Some text after the code block.";
         let expected: Vec<Block> = vec![Block {
             closed: true,
+            implicitly_closed: false,
             events: vec![
                 (
                     Event::Start(Tag::CodeBlock(CodeBlockKind::Fenced(CowStr::from("toml")))),
@@ -152,106 +743,725 @@ mod test {
                 (Event::End(TagEnd::CodeBlock), 34..77),
             ],
             span: 34..77,
-            inner_span: 42..74,
+            inner_span: Some(42..74),
+            depth: 0,
+            children: vec![],
         }];
 
-        let actual = parse_blocks(
-            content,
-            |event| matches!(event, Event::Start(Tag::CodeBlock(CodeBlockKind::Fenced(tag))) if tag == &CowStr::from("toml")),
-            |event| matches!(event, Event::End(TagEnd::CodeBlock)),
-        )?;
+        let actual = parse_blocks(
+            content,
+            |event| matches!(event, Event::Start(Tag::CodeBlock(CodeBlockKind::Fenced(tag))) if tag == &CowStr::from("toml")),
+            |event| matches!(event, Event::End(TagEnd::CodeBlock)),
+        )?;
+
+        assert_eq!(expected, actual);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_blocks_multiple() -> Result<()> {
+        let content = "\
+        First TOML block:\n\
+        ```toml\n\
+        key1 = \"value1\"\n\
+        key2 = \"value2\"\n\
+        ```\n\
+        First non-TOML block:\n\
+        ```shell\n\
+        echo test\n\
+        ```\n\
+        Second TOML block:\n\
+        ```toml\n\
+        key3 = \"value3\"\n\
+        key4 = \"value4\"\n\
+        ```";
+        let expected: Vec<Block> = vec![
+            Block {
+                closed: true,
+                implicitly_closed: false,
+                events: vec![
+                    (
+                        Event::Start(Tag::CodeBlock(CodeBlockKind::Fenced(CowStr::from("toml")))),
+                        18..61,
+                    ),
+                    (
+                        Event::Text(CowStr::from("key1 = \"value1\"\nkey2 = \"value2\"\n")),
+                        26..58,
+                    ),
+                    (Event::End(TagEnd::CodeBlock), 18..61),
+                ],
+                span: 18..61,
+                inner_span: Some(26..58),
+                depth: 0,
+                children: vec![],
+            },
+            Block {
+                closed: true,
+                implicitly_closed: false,
+                events: vec![
+                    (
+                        Event::Start(Tag::CodeBlock(CodeBlockKind::Fenced(CowStr::from("toml")))),
+                        126..169,
+                    ),
+                    (
+                        Event::Text(CowStr::from("key3 = \"value3\"\nkey4 = \"value4\"\n")),
+                        134..166,
+                    ),
+                    (Event::End(TagEnd::CodeBlock), 126..169),
+                ],
+                span: 126..169,
+                inner_span: Some(134..166),
+                depth: 0,
+                children: vec![],
+            },
+        ];
+
+        let actual = parse_blocks(
+            content,
+            |event| matches!(event, Event::Start(Tag::CodeBlock(CodeBlockKind::Fenced(tag))) if tag == &CowStr::from("toml")),
+            |event| matches!(event, Event::End(TagEnd::CodeBlock)),
+        )?;
+
+        assert_eq!(expected, actual);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_block_content() -> Result<()> {
+        let content = "\
+        Prélude.\n\
+        \n\
+        ```toml\n\
+        clé = \"värde ✓\"\n\
+        ```\n\
+        \n\
+        Épilogue.";
+
+        let blocks = parse_blocks(
+            content,
+            |event| matches!(event, Event::Start(Tag::CodeBlock(CodeBlockKind::Fenced(tag))) if tag == &CowStr::from("toml")),
+            |event| matches!(event, Event::End(TagEnd::CodeBlock)),
+        )?;
+
+        assert_eq!(1, blocks.len());
+        assert_eq!("clé = \"värde ✓\"\n", blocks[0].content(content)?);
+        assert_eq!("```toml\nclé = \"värde ✓\"\n```", blocks[0].outer(content)?);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_block_raw_events() -> Result<()> {
+        let content = "\
+        {{#tab }}\n\
+        Some &amp; <kbd>content</kbd>  \n\
+        {{#endtab }}";
+
+        let blocks = parse_blocks(
+            content,
+            |event| matches!(event, Event::Text(text) if text.starts_with("{{#tab ")),
+            |event| matches!(event, Event::Text(text) if text.starts_with("{{#endtab ")),
+        )?;
+        let raw_events = blocks[0].raw_events(content)?;
+
+        assert_eq!(
+            vec![
+                "{{#tab }}",
+                "\n",
+                "Some ",
+                "&amp;",
+                " ",
+                "<kbd>",
+                "content",
+                "</kbd>",
+                "  \n",
+                "{{#endtab }}"
+            ],
+            raw_events.iter().map(|(_, raw)| *raw).collect::<Vec<_>>()
+        );
+        assert_eq!(&Event::Text(CowStr::from("&")), raw_events[3].0);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_block_location() -> Result<()> {
+        let content = "\
+        Some text before the code block.\r\n\
+        \r\n\
+        ```toml\r\n\
+        key = \"value\"\r\n\
+        ```\r\n";
+
+        let blocks = parse_code_blocks(content, |tags| tags.contains(&"toml".to_string()))?;
+
+        assert_eq!(
+            Location {
+                start: Position { line: 3, column: 1 },
+                end: Position { line: 5, column: 4 },
+            },
+            blocks[0].location(content)
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_block_is_empty_inner() -> Result<()> {
+        let content = "\
+        {{#tab }}\n\
+        Some content.\n\
+        {{#endtab }}\n\
+        \n\
+        {{#tab }}\n";
+
+        let blocks = parse_blocks(
+            content,
+            |event| matches!(event, Event::Text(text) if text.starts_with("{{#tab ")),
+            |event| matches!(event, Event::Text(text) if text.starts_with("{{#endtab ")),
+        )?;
+
+        assert_eq!(2, blocks.len());
+        assert!(!blocks[0].is_empty_inner());
+        assert!(blocks[1].is_empty_inner());
+        assert_eq!(None, blocks[1].inner_span);
+        assert_eq!("", blocks[1].content(content)?);
+
+        let mut block = Block::new(Event::Text(CowStr::from("{{#tab }}")), 0..9);
+        assert!(block.is_empty_inner());
+
+        block.inner_span = Some(9..9);
+        assert!(block.is_empty_inner());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_blocks_in_range() -> Result<()> {
+        let content = "\
+        {{#tab }}\n\
+        First.\n\
+        {{#endtab }}\n\
+        \n\
+        {{#tab }}\n\
+        Second.\n\
+        {{#endtab }}\n\
+        \n\
+        {{#tab }}\n\
+        Third.\n\
+        {{#endtab }}\n";
+        let is_start =
+            |event: &Event| matches!(event, Event::Text(text) if text.starts_with("{{#tab "));
+        let is_end =
+            |event: &Event| matches!(event, Event::Text(text) if text.starts_with("{{#endtab "));
+
+        let all = parse_blocks(content, is_start, is_end)?;
+        let start = all[1].span.start;
+        let end = all[2].span.end;
+
+        let blocks = parse_blocks_in_range(content, start..end, is_start, is_end)?;
+        assert_eq!(2, blocks.len());
+        for (block, expected) in blocks.iter().zip(&all[1..]) {
+            assert_eq!(expected.span, block.span);
+            assert_eq!(expected.inner_span, block.inner_span);
+            assert_eq!(expected.content(content)?, block.content(content)?);
+        }
+        assert_eq!("\nSecond.\n", blocks[0].content(content)?);
+
+        // The first tab straddles the start of the range, so only its end marker is in the range.
+        let blocks = parse_blocks_in_range(content, 10..end, is_start, is_end)?;
+        assert_eq!(2, blocks.len());
+        assert_eq!(all[1].span, blocks[0].span);
+
+        // The third tab straddles the end of the range.
+        assert_eq!(
+            Err(BlockParseError::UnclosedBlock {
+                span: all[2].span.start..all[2].span.start + 9,
+            }),
+            parse_blocks_in_range(content, start..end - 13, is_start, is_end)
+        );
+
+        assert_eq!(
+            Err(BlockParseError::SpanOutOfBounds {
+                span: start..content.len() + 1,
+                len: content.len(),
+            }),
+            parse_blocks_in_range(content, start..content.len() + 1, is_start, is_end)
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_toggle_blocks() -> Result<()> {
+        let is_delimiter =
+            |event: &Event| matches!(event, Event::Text(text) if text.as_ref() == "%%%");
+
+        assert!(parse_toggle_blocks("No blocks.\n", is_delimiter).is_empty());
+
+        let content = "\
+        %%%\n\
+        First.\n\
+        %%%\n\
+        \n\
+        Between.\n\
+        \n\
+        %%%\n\
+        ```\n\
+        %%%\n\
+        ```\n\
+        %%%\n";
+
+        let blocks = parse_toggle_blocks(content, is_delimiter);
+        assert_eq!(2, blocks.len());
+        assert!(blocks.iter().all(|block| block.closed));
+        assert_eq!(0..14, blocks[0].span);
+        assert_eq!("\nFirst.\n", blocks[0].content(content)?);
+        assert_eq!("```\n%%%\n```", blocks[1].content(content)?);
+        for block in &blocks {
+            assert_block_invariants(content, block);
+        }
+
+        // The last delimiter of an odd number of delimiters opens an unclosed block.
+        let content = format!("{}\n%%%\nLast.\n", content);
+        let blocks = parse_toggle_blocks(&content, is_delimiter);
+        assert_eq!(3, blocks.len());
+        assert!(blocks[1].closed);
+        assert!(!blocks[2].closed);
+        assert!(blocks[2]
+            .events
+            .iter()
+            .any(|(event, _)| matches!(event, Event::Text(text) if text.as_ref() == "Last.")));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_blocks_empty() -> Result<()> {
+        let content = "\
+        {{#tab }}\n\
+        \n\
+        {{#endtab }}\n\
+        \n\
+        {{#tab }}\n\
+        \x20  \t\n\
+        {{#endtab }}\n\
+        \n\
+        {{#tab }}\n\
+        {{#endtab }}\n";
+
+        let blocks = parse_blocks(
+            content,
+            |event| matches!(event, Event::Text(text) if text.starts_with("{{#tab ")),
+            |event| matches!(event, Event::Text(text) if text.starts_with("{{#endtab ")),
+        )?;
+
+        assert_eq!(3, blocks.len());
+        assert_eq!(
+            vec![None, None, Some("\n")],
+            blocks
+                .iter()
+                .map(|block| block.inner_span.clone().map(|span| &content[span]))
+                .collect::<Vec<_>>()
+        );
+        for block in &blocks {
+            assert_block_invariants(content, block);
+            assert!(block.content(content)?.trim().is_empty());
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_block_extract_marker() -> Result<()> {
+        let content = "\
+        {{#tabs global=\"example\" default=\"Say \\\"hi\\\"\" }}\n\
+        {{#tab name=\"Tab 1\" }}\n\
+        Some content.\n\
+        {{#endtab }}\n\
+        {{#endtabs }}\n\
+        \n\
+        {{#tabs}}\n\
+        {{#endtabs }}\n";
+
+        let blocks = parse_blocks(
+            content,
+            |event| matches!(event, Event::Text(text) if text.starts_with("{{#tabs")),
+            |event| matches!(event, Event::Text(text) if text.starts_with("{{#endtabs ")),
+        )?;
+
+        assert_eq!(2, blocks.len());
+        assert_eq!(
+            vec![Some("tabs"), Some("tabs")],
+            blocks
+                .iter()
+                .map(|block| block.extract_marker_name(content))
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(
+            vec![
+                Some("global=\"example\" default=\"Say \\\"hi\\\"\""),
+                Some("")
+            ],
+            blocks
+                .iter()
+                .map(|block| block.extract_marker_attributes(content))
+                .collect::<Vec<_>>()
+        );
+
+        let attributes = blocks[0]
+            .extract_marker_attributes(content)
+            .map(|attributes| MarkerAttributes::parse(&format!("{{{{#tabs {}}}}}", attributes)))
+            .transpose()?;
+        assert_eq!(
+            Some("Say \"hi\""),
+            attributes
+                .as_ref()
+                .and_then(|attributes| attributes.get("default"))
+        );
+
+        // Blocks started by other events than markers have no marker.
+        let content = "*Emphasis* text.";
+        let blocks = parse_blocks(
+            content,
+            |event| matches!(event, Event::Start(Tag::Emphasis)),
+            |event| matches!(event, Event::End(TagEnd::Emphasis)),
+        )?;
+        assert_eq!(None, blocks[0].extract_marker_name(content));
+        assert_eq!(None, blocks[0].extract_marker_attributes(content));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_block_content_out_of_bounds() -> Result<()> {
+        let content = "é";
+        let mut block = Block::new(Event::Text(CowStr::from("é")), 0..2);
+
+        block.inner_span = Some(0..1);
+        assert!(block.content(content).is_err());
+
+        block.inner_span = Some(0..3);
+        assert!(block.content(content).is_err());
+
+        assert_eq!("é", block.outer(content)?);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_blocks_nested() -> Result<()> {
+        let content = "*a **sentence** with **some** words*";
+
+        let actual = parse_blocks(
+            content,
+            |event| {
+                matches!(
+                    event,
+                    Event::Start(Tag::Emphasis) | Event::Start(Tag::Strong)
+                )
+            },
+            |event| {
+                matches!(
+                    event,
+                    Event::End(TagEnd::Emphasis) | Event::End(TagEnd::Strong)
+                )
+            },
+        );
+
+        assert_eq!(
+            BlockParseError::UnexpectedNestedBlock {
+                span: 3..15,
+                parent_span: 0..36
+            },
+            actual.unwrap_err()
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_nested_blocks() -> Result<()> {
+        let content = "*a **sentence** with **some** words*";
+
+        let actual = parse_nested_blocks(
+            content,
+            |event| {
+                matches!(
+                    event,
+                    Event::Start(Tag::Emphasis) | Event::Start(Tag::Strong)
+                )
+            },
+            |event| {
+                matches!(
+                    event,
+                    Event::End(TagEnd::Emphasis) | Event::End(TagEnd::Strong)
+                )
+            },
+            2,
+        )?;
+
+        assert_eq!(1, actual.len());
+
+        let block = &actual[0];
+        assert!(block.closed);
+        assert_eq!(0, block.depth);
+        assert_eq!(0..36, block.span);
+        assert_eq!(Some(1..35), block.inner_span);
+        assert_eq!(2, block.children.len());
+
+        assert_eq!(1, block.children[0].depth);
+        assert_eq!(3..15, block.children[0].span);
+        assert_eq!(Some(5..13), block.children[0].inner_span);
+        assert_eq!(
+            vec![
+                (Event::Start(Tag::Strong), 3..15),
+                (Event::Text(CowStr::from("sentence")), 5..13),
+                (Event::End(TagEnd::Strong), 3..15),
+            ],
+            block.children[0].events
+        );
+
+        assert_eq!(1, block.children[1].depth);
+        assert_eq!(21..29, block.children[1].span);
+        assert_eq!(Some(23..27), block.children[1].inner_span);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_nested_blocks_max_depth() -> Result<()> {
+        let content = "*a **sentence** with ***some*** words*";
+
+        let actual = parse_nested_blocks(
+            content,
+            |event| {
+                matches!(
+                    event,
+                    Event::Start(Tag::Emphasis) | Event::Start(Tag::Strong)
+                )
+            },
+            |event| {
+                matches!(
+                    event,
+                    Event::End(TagEnd::Emphasis) | Event::End(TagEnd::Strong)
+                )
+            },
+            2,
+        );
+
+        assert_eq!(
+            BlockParseError::UnexpectedNestedBlock {
+                span: 22..30,
+                parent_span: 21..31
+            },
+            actual.unwrap_err()
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_blocks_strict() -> Result<()> {
+        let is_start =
+            |event: &Event| matches!(event, Event::Text(text) if text.starts_with("{{#tab "));
+        let is_end =
+            |event: &Event| matches!(event, Event::Text(text) if text.starts_with("{{#endtab "));
+
+        let content = "\
+        {{#tab }}\n\
+        Some content.\n\
+        {{#endtab }}\n\
+        \n\
+        {{#tab }}\n\
+        Unclosed content.";
+
+        let lenient = parse_blocks(content, is_start, is_end)?;
+        assert_eq!(2, lenient.len());
+        assert!(!lenient[1].closed);
+
+        let strict = parse_blocks_strict(content, is_start, is_end);
+        assert_eq!(
+            BlockParseError::UnclosedBlock { span: 38..47 },
+            strict.unwrap_err()
+        );
+
+        let content = "{{#tab }}\nSome content.\n{{#endtab }}";
+        assert_eq!(1, parse_blocks_strict(content, is_start, is_end)?.len());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_blocks_with_gaps() -> Result<()> {
+        let content = "\
+        Before.\n\
+        \n\
+        ```toml\n\
+        key = \"value\"\n\
+        ```\n\
+        \n\
+        Between.\n\
+        \n\
+        ```toml\n\
+        other = \"value\"\n\
+        ```\n\
+        \n\
+        After.";
+
+        let (blocks, gaps) = parse_blocks_with_gaps(
+            content,
+            |event| matches!(event, Event::Start(Tag::CodeBlock(CodeBlockKind::Fenced(tag))) if tag == &CowStr::from("toml")),
+            |event| matches!(event, Event::End(TagEnd::CodeBlock)),
+        )?;
+
+        assert_eq!(2, blocks.len());
+        assert_eq!(
+            vec!["Before.\n\n", "\n\nBetween.\n\n", "\n\nAfter."],
+            gaps.iter()
+                .map(|gap| &content[gap.clone()])
+                .collect::<Vec<_>>()
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_blocks_with_gaps_edges() -> Result<()> {
+        let is_start = |event: &Event| matches!(event, Event::Start(Tag::CodeBlock(_)));
+        let is_end = |event: &Event| matches!(event, Event::End(TagEnd::CodeBlock));
+
+        let content = "No blocks at all.";
+        let (blocks, gaps) = parse_blocks_with_gaps(content, is_start, is_end)?;
+        assert!(blocks.is_empty());
+        assert_eq!(vec![0..17], gaps);
 
-        assert_eq!(expected, actual);
+        let content = "```\ncode\n```";
+        let (blocks, gaps) = parse_blocks_with_gaps(content, is_start, is_end)?;
+        assert_eq!(1, blocks.len());
+        assert!(gaps.is_empty());
+
+        let (blocks, gaps) = parse_blocks_with_gaps("", is_start, is_end)?;
+        assert!(blocks.is_empty());
+        assert!(gaps.is_empty());
 
         Ok(())
     }
 
     #[test]
-    fn test_parse_blocks_multiple() -> Result<()> {
+    fn test_parse_blocks_multi() -> Result<()> {
         let content = "\
-        First TOML block:\n\
         ```toml\n\
-        key1 = \"value1\"\n\
-        key2 = \"value2\"\n\
-        ```\n\
-        First non-TOML block:\n\
-        ```shell\n\
-        echo test\n\
+        key = \"value\"\n\
         ```\n\
-        Second TOML block:\n\
+        \n\
+        {{#note }}\n\
+        Some note.\n\
+        {{#endnote }}\n\
+        \n\
         ```toml\n\
-        key3 = \"value3\"\n\
-        key4 = \"value4\"\n\
+        other = \"value\"\n\
         ```";
-        let expected: Vec<Block> = vec![
-            Block {
-                closed: true,
-                events: vec![
-                    (
-                        Event::Start(Tag::CodeBlock(CodeBlockKind::Fenced(CowStr::from("toml")))),
-                        18..61,
-                    ),
-                    (
-                        Event::Text(CowStr::from("key1 = \"value1\"\nkey2 = \"value2\"\n")),
-                        26..58,
-                    ),
-                    (Event::End(TagEnd::CodeBlock), 18..61),
-                ],
-                span: 18..61,
-                inner_span: 26..58,
-            },
-            Block {
-                closed: true,
-                events: vec![
-                    (
-                        Event::Start(Tag::CodeBlock(CodeBlockKind::Fenced(CowStr::from("toml")))),
-                        126..169,
-                    ),
-                    (
-                        Event::Text(CowStr::from("key3 = \"value3\"\nkey4 = \"value4\"\n")),
-                        134..166,
-                    ),
-                    (Event::End(TagEnd::CodeBlock), 126..169),
-                ],
-                span: 126..169,
-                inner_span: 134..166,
-            },
-        ];
 
-        let actual = parse_blocks(
+        let is_toml_start = |event: &Event| matches!(event, Event::Start(Tag::CodeBlock(CodeBlockKind::Fenced(tag))) if tag == &CowStr::from("toml"));
+        let is_toml_end = |event: &Event| matches!(event, Event::End(TagEnd::CodeBlock));
+        let is_note_start =
+            |event: &Event| matches!(event, Event::Text(text) if text.starts_with("{{#note "));
+        let is_note_end =
+            |event: &Event| matches!(event, Event::Text(text) if text.starts_with("{{#endnote "));
+
+        let actual = parse_blocks_multi(
             content,
-            |event| matches!(event, Event::Start(Tag::CodeBlock(CodeBlockKind::Fenced(tag))) if tag == &CowStr::from("toml")),
-            |event| matches!(event, Event::End(TagEnd::CodeBlock)),
+            &[
+                (&is_note_start, &is_note_end),
+                (&is_toml_start, &is_toml_end),
+            ],
         )?;
 
-        assert_eq!(expected, actual);
+        assert_eq!(
+            vec![(1, 0..25), (0, 27..62), (1, 64..91)],
+            actual
+                .iter()
+                .map(|(index, block)| (*index, block.span.clone()))
+                .collect::<Vec<_>>()
+        );
+        assert!(actual.iter().all(|(_, block)| block.closed));
+        assert_eq!("\nSome note.\n", actual[1].1.content(content)?);
 
         Ok(())
     }
 
     #[test]
-    fn test_parse_blocks_nested() -> Result<()> {
+    fn test_parse_blocks_multi_code_block_marker() -> Result<()> {
+        let content = "\
+        ```md\n\
+        {{#note }}\n\
+        Fenced.\n\
+        ```\n\
+        \n\
+        \x20   {{#tip }}\n\
+        \x20   Indented.\n\
+        \n\
+        {{#tip }}\n\
+        \n\
+        ```md\n\
+        {{#endtip }}\n\
+        ```\n\
+        \n\
+        {{#endtip }}";
+
+        let is_note_start =
+            |event: &Event| matches!(event, Event::Text(text) if text.starts_with("{{#note "));
+        let is_note_end =
+            |event: &Event| matches!(event, Event::Text(text) if text.starts_with("{{#endnote "));
+        let is_tip_start =
+            |event: &Event| matches!(event, Event::Text(text) if text.starts_with("{{#tip "));
+        let is_tip_end =
+            |event: &Event| matches!(event, Event::Text(text) if text.starts_with("{{#endtip "));
+
+        let actual = parse_blocks_multi(
+            content,
+            &[(&is_note_start, &is_note_end), (&is_tip_start, &is_tip_end)],
+        )?;
+
+        // Markers in fenced and indented code blocks are shown as code, they do not open or close
+        // blocks.
+        assert_eq!(1, actual.len());
+        assert_eq!(1, actual[0].0);
+        assert!(actual[0].1.closed);
+        assert_eq!(
+            "```md\n{{#endtip }}\n```\n\n",
+            actual[0].1.content(content)?
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_blocks_multi_overlapping() -> Result<()> {
         let content = "*a **sentence** with **some** words*";
 
-        let actual = parse_blocks(
+        let is_emphasis_start = |event: &Event| matches!(event, Event::Start(Tag::Emphasis));
+        let is_emphasis_end = |event: &Event| matches!(event, Event::End(TagEnd::Emphasis));
+        let is_strong_start = |event: &Event| matches!(event, Event::Start(Tag::Strong));
+        let is_strong_end = |event: &Event| matches!(event, Event::End(TagEnd::Strong));
+
+        let actual = parse_blocks_multi(
             content,
-            |event| {
-                matches!(
-                    event,
-                    Event::Start(Tag::Emphasis) | Event::Start(Tag::Strong)
-                )
-            },
-            |event| {
-                matches!(
-                    event,
-                    Event::End(TagEnd::Emphasis) | Event::End(TagEnd::Strong)
-                )
-            },
+            &[
+                (&is_emphasis_start, &is_emphasis_end),
+                (&is_strong_start, &is_strong_end),
+            ],
         );
 
         assert_eq!(
-            "Block is not closed. Nested blocks are not supported.",
-            format!("{}", actual.unwrap_err().root_cause())
+            BlockParseError::UnexpectedNestedBlock {
+                span: 3..15,
+                parent_span: 0..36
+            },
+            actual.unwrap_err()
         );
 
         Ok(())
@@ -274,6 +1484,7 @@ mod test {
         let expected: Vec<Block> = vec![
             Block {
                 closed: true,
+                implicitly_closed: false,
                 events: vec![
                     (Event::Text(CowStr::from("{{#tab }}")), 0..9),
                     (Event::SoftBreak, 9..10),
@@ -282,10 +1493,13 @@ mod test {
                     (Event::Text(CowStr::from("{{#endtab }}")), 24..36),
                 ],
                 span: 0..36,
-                inner_span: 9..24,
+                inner_span: Some(9..24),
+                depth: 0,
+                children: vec![],
             },
             Block {
                 closed: true,
+                implicitly_closed: false,
                 events: vec![
                     (Event::Text(CowStr::from("{{#tab }}")), 37..46),
                     (
@@ -297,7 +1511,9 @@ mod test {
                     (Event::Text(CowStr::from("{{#endtab }}")), 76..88),
                 ],
                 span: 37..88,
-                inner_span: 48..74,
+                inner_span: Some(48..74),
+                depth: 0,
+                children: vec![],
             },
         ];
 
@@ -311,4 +1527,428 @@ mod test {
 
         Ok(())
     }
+
+    #[test]
+    fn test_block_events() -> Result<()> {
+        let content = "\
+        {{#tab }}\n\
+        Some content.\n\
+        {{#endtab }}\n\
+        \n\
+        {{#tab }}\n\
+        Not closed.";
+
+        let is_start =
+            |event: &Event| matches!(event, Event::Text(text) if text.starts_with("{{#tab "));
+        let is_end =
+            |event: &Event| matches!(event, Event::Text(text) if text.starts_with("{{#endtab "));
+
+        let closed = &parse_blocks(&content[..36], is_start, is_end)?[0];
+        assert_eq!(
+            &(Event::Text(CowStr::from("{{#tab }}")), 0..9),
+            closed.start_event()
+        );
+        assert_eq!(
+            Some(&(Event::Text(CowStr::from("{{#endtab }}")), 24..36)),
+            closed.end_event()
+        );
+
+        let blocks = BlocksIter::new(
+            Parser::new(content).into_offset_iter(),
+            is_start,
+            is_end,
+            usize::MAX,
+        )
+        .collect::<Result<Vec<_>, _>>()?;
+        assert_eq!(2, blocks.len());
+        assert!(!blocks[1].closed);
+        assert_eq!(
+            &(Event::Text(CowStr::from("{{#tab }}")), 38..47),
+            blocks[1].start_event()
+        );
+        assert_eq!(None, blocks[1].end_event());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_blocks_text_paragraphs() -> Result<()> {
+        let content = "\
+        {{#tab }}\n\
+        \n\
+        Some content.\n\
+        \n\
+        {{#endtab }}";
+
+        let actual = parse_blocks(
+            content,
+            |event| matches!(event, Event::Text(text) if text.starts_with("{{#tab ")),
+            |event| matches!(event, Event::Text(text) if text.starts_with("{{#endtab ")),
+        )?;
+
+        assert_eq!(0..38, actual[0].span);
+        assert_eq!("Some content.\n\n", actual[0].content(content)?);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_blocks_with_parser() -> Result<()> {
+        let content = "\
+        {{#tab }}\n\
+        \n\
+        | Key | Value |\n\
+        | --- | ----- |\n\
+        | a   | 1     |\n\
+        \n\
+        {{#endtab }}";
+
+        let is_start =
+            |event: &Event| matches!(event, Event::Text(text) if text.starts_with("{{#tab "));
+        let is_end =
+            |event: &Event| matches!(event, Event::Text(text) if text.starts_with("{{#endtab "));
+
+        let default = parse_blocks(content, is_start, is_end)?;
+        assert!(!default[0]
+            .events
+            .iter()
+            .any(|(event, _)| matches!(event, Event::Start(Tag::Table(_)))));
+
+        let tables = parse_blocks_with_parser(
+            Parser::new_ext(content, Options::ENABLE_TABLES).into_offset_iter(),
+            is_start,
+            is_end,
+        )?;
+        assert!(tables[0]
+            .events
+            .iter()
+            .any(|(event, _)| matches!(event, Event::Start(Tag::Table(_)))));
+        assert_eq!(
+            "| Key | Value |\n| --- | ----- |\n| a   | 1     |\n\n",
+            tables[0].content(content)?
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_blocks_code_block_marker() -> Result<()> {
+        let content = "\
+        {{#tab }}\n\
+        \n\
+        ```md\n\
+        {{#tab }}\n\
+        Example.\n\
+        {{#endtab }}\n\
+        ```\n\
+        \n\
+        {{#endtab }}";
+
+        let is_start =
+            |event: &Event| matches!(event, Event::Text(text) if text.starts_with("{{#tab "));
+        let is_end =
+            |event: &Event| matches!(event, Event::Text(text) if text.starts_with("{{#endtab "));
+
+        let actual = parse_nested_blocks(content, is_start, is_end, usize::MAX)?;
+
+        assert_eq!(1, actual.len());
+        assert!(actual[0].closed);
+        assert!(actual[0].children.is_empty());
+        assert_eq!(
+            "```md\n{{#tab }}\nExample.\n{{#endtab }}\n```\n\n",
+            actual[0].content(content)?
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_blocks_crlf() -> Result<()> {
+        let content = "\
+        {{#tab }}\r\n\
+        Sömé cöntént.\r\n\
+        {{#endtab }}\r\n\
+        {{#tab }}\r\n\
+        \r\n\
+        ```rust\r\n\
+        let ä = 1 + 2;\r\n\
+        ```\r\n\
+        \r\n\
+        {{#endtab }}\r\n";
+
+        let is_start =
+            |event: &Event| matches!(event, Event::Text(text) if text.starts_with("{{#tab "));
+        let is_end =
+            |event: &Event| matches!(event, Event::Text(text) if text.starts_with("{{#endtab "));
+
+        let actual = parse_blocks(content, is_start, is_end)?;
+
+        assert_eq!(2, actual.len());
+        assert_eq!("\r\nSömé cöntént.\r\n", actual[0].content(content)?);
+        assert_eq!(
+            "```rust\r\nlet ä = 1 + 2;\r\n```",
+            actual[1].content(content)?
+        );
+
+        for block in &actual {
+            let inner_span = block.inner_span.clone().unwrap_or_default();
+            assert!(content.is_char_boundary(inner_span.start));
+            assert!(content.is_char_boundary(inner_span.end));
+            assert!(!block.outer(content)?.ends_with('\r'));
+            assert!(!block.content(content)?.ends_with('\r'));
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_blocks_lenient() -> Result<()> {
+        let content = "\
+        {{#tab }}\n\
+        First.\n\
+        \n\
+        {{#tab }}\n\
+        Second.\n\
+        {{#endtab }}\n\
+        \n\
+        After.";
+
+        let is_start =
+            |event: &Event| matches!(event, Event::Text(text) if text.starts_with("{{#tab "));
+        let is_end =
+            |event: &Event| matches!(event, Event::Text(text) if text.starts_with("{{#endtab "));
+
+        let actual = parse_blocks_lenient(content, is_start, is_end);
+
+        assert_eq!(2, actual.len());
+        assert!(actual[0].implicitly_closed);
+        assert_eq!(None, actual[0].end_event());
+        assert_eq!(
+            Some(&(Event::Text(CowStr::from("{{#endtab }}")), 36..48)),
+            actual[1].end_event()
+        );
+        assert!(actual[0].closed);
+        assert_eq!(0..17, actual[0].span);
+        assert_eq!("{{#tab }}\nFirst.\n", actual[0].outer(content)?);
+        assert_eq!("\nFirst.\n", actual[0].content(content)?);
+        assert!(actual[1].closed);
+        assert_eq!(18..48, actual[1].span);
+        assert_eq!("\nSecond.\n", actual[1].content(content)?);
+
+        assert!(matches!(
+            parse_blocks(content, is_start, is_end),
+            Err(BlockParseError::UnexpectedNestedBlock { .. })
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_count_blocks() {
+        let is_start =
+            |event: &Event| matches!(event, Event::Text(text) if text.starts_with("{{#tab "));
+        let is_end =
+            |event: &Event| matches!(event, Event::Text(text) if text.starts_with("{{#endtab "));
+
+        for content in [
+            "",
+            "{{#tab }}\nFirst.\n{{#endtab }}\n\n{{#tab }}\nSecond.\n{{#endtab }}",
+            "{{#tab }}\nUnclosed.",
+            "```markdown\n{{#tab }}\n```\n\n{{#tab }}\n{{#endtab }}",
+            "{{#tab }}\n{{#tab }}\n{{#endtab }}\n{{#endtab }}",
+        ] {
+            assert_eq!(
+                parse_blocks(content, is_start, is_end).map(|blocks| blocks.len()),
+                count_blocks(content, is_start, is_end)
+            );
+        }
+    }
+
+    #[test]
+    fn test_iter_blocks() -> Result<()> {
+        let content = "\
+        {{#tab }}\n\
+        First.\n\
+        {{#endtab }}\n\
+        \n\
+        {{#tab }}\n\
+        Second.\n\
+        {{#endtab }}\n\
+        \n\
+        {{#tab }}\n\
+        {{#tab }}\n\
+        {{#endtab }}";
+
+        let is_start =
+            |event: &Event| matches!(event, Event::Text(text) if text.starts_with("{{#tab "));
+        let is_end =
+            |event: &Event| matches!(event, Event::Text(text) if text.starts_with("{{#endtab "));
+
+        let consumed = Cell::new(0);
+        let events = Parser::new(content)
+            .into_offset_iter()
+            .inspect(|(_, span)| consumed.set(span.end));
+        let mut blocks = BlocksIter::new(events, is_start, is_end, 1);
+
+        let first = blocks.next().unwrap()?;
+        assert_eq!("\nFirst.\n", first.content(content)?);
+        assert_eq!(first.span.end, consumed.get());
+
+        let second = blocks.next().unwrap()?;
+        assert_eq!("\nSecond.\n", second.content(content)?);
+        assert_eq!(second.span.end, consumed.get());
+
+        assert!(matches!(
+            blocks.next(),
+            Some(Err(BlockParseError::UnexpectedNestedBlock { .. }))
+        ));
+        assert!(blocks.next().is_none());
+
+        assert_eq!(
+            2,
+            iter_blocks(content, is_start, is_end)
+                .take_while(Result::is_ok)
+                .count()
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_blocks_limits() -> Result<()> {
+        let content = "\
+        {{#tab }}\n\
+        First.\n\
+        {{#endtab }}\n\
+        \n\
+        {{#tab }}\n\
+        Second, which is longer.\n\
+        {{#endtab }}";
+
+        let is_start =
+            |event: &Event| matches!(event, Event::Text(text) if text.starts_with("{{#tab "));
+        let is_end =
+            |event: &Event| matches!(event, Event::Text(text) if text.starts_with("{{#endtab "));
+        let parse = |limits| {
+            BlocksIter::new(Parser::new(content).into_offset_iter(), is_start, is_end, 1)
+                .with_limits(limits)
+                .collect::<Result<Vec<_>, _>>()
+        };
+
+        assert_eq!(2, parse(BlockLimits::default())?.len());
+        assert_eq!(
+            Err(BlockParseError::TooManyBlocks {
+                span: 31..40,
+                max: 1
+            }),
+            parse(BlockLimits {
+                max_blocks: Some(1),
+                ..Default::default()
+            })
+        );
+        assert_eq!(
+            Err(BlockParseError::BlockTooLarge {
+                span: 31..65,
+                max: 30
+            }),
+            parse(BlockLimits {
+                max_block_size: Some(30),
+                ..Default::default()
+            })
+        );
+
+        Ok(())
+    }
+
+    fn assert_block_invariants(content: &str, block: &Block) {
+        let span = &block.span;
+        assert!(span.start <= span.end, "{:?} in {:?}", span, content);
+        assert!(span.end <= content.len(), "{:?} in {:?}", span, content);
+        assert!(content.is_char_boundary(span.start) && content.is_char_boundary(span.end));
+
+        if let Some(inner_span) = &block.inner_span {
+            assert!(
+                inner_span.start < inner_span.end,
+                "{:?} in {:?}",
+                inner_span,
+                content
+            );
+            assert!(
+                span.start <= inner_span.start && inner_span.end <= span.end,
+                "{:?} not in {:?} in {:?}",
+                inner_span,
+                span,
+                content
+            );
+            assert!(
+                content.is_char_boundary(inner_span.start)
+                    && content.is_char_boundary(inner_span.end)
+            );
+        }
+
+        assert!(block.content(content).is_ok());
+        assert!(block.outer(content).is_ok());
+
+        for child in &block.children {
+            assert_block_invariants(content, child);
+        }
+    }
+
+    // Randomized inputs built from fragments with special meaning in markdown or for the markers,
+    // the `fuzz` crate runs the same checks on arbitrary input.
+    #[test]
+    fn test_parse_blocks_random() {
+        let fragments = [
+            "{{#tab }}",
+            "{{#endtab }}",
+            "\n",
+            "\r\n",
+            " ",
+            "    ",
+            "*",
+            "**",
+            "`",
+            "```",
+            "~~~",
+            "> ",
+            "- ",
+            "1. ",
+            "# ",
+            "|",
+            "---",
+            "<div>",
+            "</div>",
+            "[a](b)",
+            "\\",
+            "é",
+            "日本",
+            "text",
+        ];
+        let is_tab_start =
+            |event: &Event| matches!(event, Event::Text(text) if text.starts_with("{{#tab "));
+        let is_tab_end =
+            |event: &Event| matches!(event, Event::Text(text) if text.starts_with("{{#endtab "));
+        let is_emphasis_start = |event: &Event| matches!(event, Event::Start(Tag::Emphasis));
+        let is_emphasis_end = |event: &Event| matches!(event, Event::End(TagEnd::Emphasis));
+
+        let mut rng = fastrand::Rng::with_seed(0);
+        for _ in 0..2000 {
+            let content = (0..rng.usize(1..40))
+                .map(|_| fragments[rng.usize(..fragments.len())])
+                .collect::<String>();
+
+            for max_depth in 1..=3 {
+                let results = [
+                    parse_nested_blocks(&content, is_tab_start, is_tab_end, max_depth),
+                    parse_nested_blocks(&content, is_emphasis_start, is_emphasis_end, max_depth),
+                ];
+
+                for blocks in results.iter().flatten() {
+                    for block in blocks {
+                        assert_block_invariants(&content, block);
+                    }
+                }
+            }
+        }
+    }
 }
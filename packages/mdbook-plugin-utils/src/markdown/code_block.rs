@@ -1,7 +1,9 @@
-use anyhow::Result;
 use pulldown_cmark::{CodeBlockKind, Event, Tag, TagEnd};
 
-use crate::markdown::block::{parse_blocks, Block};
+use crate::markdown::{
+    block::{parse_blocks, Block},
+    error::BlockParseError,
+};
 
 fn is_code_block_start<IsTagsFn>(is_tags: IsTagsFn) -> Box<dyn Fn(&Event) -> bool>
 where
@@ -24,7 +26,10 @@ fn is_code_block_end(event: &Event) -> bool {
     matches!(event, Event::End(TagEnd::CodeBlock))
 }
 
-pub fn parse_code_blocks<IsTagsFn>(content: &str, is_tags: IsTagsFn) -> Result<Vec<Block>>
+pub fn parse_code_blocks<IsTagsFn>(
+    content: &str,
+    is_tags: IsTagsFn,
+) -> Result<Vec<Block<'_>>, BlockParseError>
 where
     IsTagsFn: Fn(Vec<String>) -> bool + 'static,
 {
@@ -0,0 +1,137 @@
+use std::{error::Error, fmt, ops::Range};
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum BlockParseError {
+    UnexpectedNestedBlock {
+        span: Range<usize>,
+        parent_span: Range<usize>,
+    },
+    UnclosedBlock {
+        span: Range<usize>,
+    },
+    SpanOutOfBounds {
+        span: Range<usize>,
+        len: usize,
+    },
+    TooManyBlocks {
+        span: Range<usize>,
+        max: usize,
+    },
+    BlockTooLarge {
+        span: Range<usize>,
+        max: usize,
+    },
+}
+
+impl BlockParseError {
+    pub fn span(&self) -> &Range<usize> {
+        match self {
+            BlockParseError::UnexpectedNestedBlock { span, .. } => span,
+            BlockParseError::UnclosedBlock { span } => span,
+            BlockParseError::SpanOutOfBounds { span, .. } => span,
+            BlockParseError::TooManyBlocks { span, .. } => span,
+            BlockParseError::BlockTooLarge { span, .. } => span,
+        }
+    }
+
+    pub fn with_offset(mut self, offset: usize) -> Self {
+        let shift = |span: &mut Range<usize>| *span = (span.start + offset)..(span.end + offset);
+
+        match &mut self {
+            BlockParseError::UnexpectedNestedBlock { span, parent_span } => {
+                shift(span);
+                shift(parent_span);
+            }
+            BlockParseError::UnclosedBlock { span }
+            | BlockParseError::SpanOutOfBounds { span, .. }
+            | BlockParseError::TooManyBlocks { span, .. }
+            | BlockParseError::BlockTooLarge { span, .. } => shift(span),
+        }
+
+        self
+    }
+}
+
+impl fmt::Display for BlockParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BlockParseError::UnexpectedNestedBlock { span, parent_span } => write!(
+                f,
+                "Block at {:?} starts before block at {:?} is closed. Nested blocks are not supported.",
+                span, parent_span
+            ),
+            BlockParseError::UnclosedBlock { span } => {
+                write!(f, "Block at {:?} is not closed.", span)
+            }
+            BlockParseError::SpanOutOfBounds { span, len } => write!(
+                f,
+                "Span {:?} is out of bounds or not on a character boundary of the source (length {}).",
+                span, len
+            ),
+            BlockParseError::TooManyBlocks { span, max } => write!(
+                f,
+                "Block at {:?} exceeds the maximum number of blocks ({}).",
+                span, max
+            ),
+            BlockParseError::BlockTooLarge { span, max } => write!(
+                f,
+                "Block at {:?} exceeds the maximum block size ({} bytes).",
+                span, max
+            ),
+        }
+    }
+}
+
+impl Error for BlockParseError {}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum MarkerParseError {
+    MissingDelimiters { span: Range<usize> },
+    MissingName { span: Range<usize> },
+    UnexpectedCharacter { span: Range<usize> },
+    MissingQuotes { span: Range<usize> },
+    UnclosedQuote { span: Range<usize> },
+    DuplicateAttribute { span: Range<usize> },
+}
+
+impl MarkerParseError {
+    pub fn span(&self) -> &Range<usize> {
+        match self {
+            MarkerParseError::MissingDelimiters { span } => span,
+            MarkerParseError::MissingName { span } => span,
+            MarkerParseError::UnexpectedCharacter { span } => span,
+            MarkerParseError::MissingQuotes { span } => span,
+            MarkerParseError::UnclosedQuote { span } => span,
+            MarkerParseError::DuplicateAttribute { span } => span,
+        }
+    }
+}
+
+impl fmt::Display for MarkerParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MarkerParseError::MissingDelimiters { span } => write!(
+                f,
+                "Marker at {:?} should start with `{{{{#` and end with `}}}}`.",
+                span
+            ),
+            MarkerParseError::MissingName { span } => {
+                write!(f, "Marker at {:?} does not have a name.", span)
+            }
+            MarkerParseError::UnexpectedCharacter { span } => {
+                write!(f, "Unexpected character at {:?} in marker.", span)
+            }
+            MarkerParseError::MissingQuotes { span } => {
+                write!(f, "Attribute value at {:?} should be quoted.", span)
+            }
+            MarkerParseError::UnclosedQuote { span } => {
+                write!(f, "Attribute value at {:?} is not closed.", span)
+            }
+            MarkerParseError::DuplicateAttribute { span } => {
+                write!(f, "Attribute at {:?} is already defined.", span)
+            }
+        }
+    }
+}
+
+impl Error for MarkerParseError {}
@@ -0,0 +1,214 @@
+use pulldown_cmark::{CodeBlockKind, CowStr, Event, Tag, TagEnd};
+
+// Longest run of backticks, so code is fenced with more backticks than it contains.
+fn backtick_run(text: &str) -> usize {
+    text.split(|c| c != '`')
+        .map(str::len)
+        .max()
+        .unwrap_or_default()
+}
+
+// Block elements start on a new line, separated from previous content by a blank line.
+fn start_block(markdown: &mut String) {
+    if markdown.is_empty() {
+        return;
+    }
+
+    while !markdown.ends_with("\n\n") {
+        markdown.push('\n');
+    }
+}
+
+fn end_line(markdown: &mut String) {
+    if !markdown.is_empty() && !markdown.ends_with('\n') {
+        markdown.push('\n');
+    }
+}
+
+// Serializes events back to markdown. Text is not escaped, as it is usually taken from markers
+// or content, and containers without inline syntax (e.g. lists and tables) only keep their text.
+pub fn events_to_markdown(events: &[Event]) -> String {
+    let mut markdown = String::new();
+    let mut links: Vec<(CowStr, CowStr)> = vec![];
+    let mut code_block: Option<(CodeBlockKind, String)> = None;
+
+    for event in events {
+        if let Some((_, code)) = &mut code_block {
+            match event {
+                Event::End(TagEnd::CodeBlock) => {}
+                Event::Text(text) | Event::Html(text) => {
+                    code.push_str(text);
+                    continue;
+                }
+                _ => continue,
+            }
+        }
+
+        match event {
+            Event::Start(Tag::Paragraph) => start_block(&mut markdown),
+            Event::End(TagEnd::Paragraph) => end_line(&mut markdown),
+            Event::Start(Tag::Heading { level, .. }) => {
+                start_block(&mut markdown);
+                markdown.push_str(&"#".repeat(*level as usize));
+                markdown.push(' ');
+            }
+            Event::End(TagEnd::Heading(_)) => end_line(&mut markdown),
+            Event::Start(Tag::CodeBlock(kind)) => {
+                start_block(&mut markdown);
+                code_block = Some((kind.clone(), String::new()));
+            }
+            Event::End(TagEnd::CodeBlock) => {
+                if let Some((kind, code)) = code_block.take() {
+                    match kind {
+                        CodeBlockKind::Fenced(info) => {
+                            let fence = "`".repeat(backtick_run(&code).max(2) + 1);
+                            markdown.push_str(&format!("{}{}\n{}", fence, info, code));
+                            end_line(&mut markdown);
+                            markdown.push_str(&fence);
+                            markdown.push('\n');
+                        }
+                        CodeBlockKind::Indented => {
+                            for line in code.lines() {
+                                markdown.push_str("    ");
+                                markdown.push_str(line);
+                                markdown.push('\n');
+                            }
+                        }
+                    }
+                }
+            }
+            Event::Start(Tag::Emphasis) | Event::End(TagEnd::Emphasis) => markdown.push('*'),
+            Event::Start(Tag::Strong) | Event::End(TagEnd::Strong) => markdown.push_str("**"),
+            Event::Start(Tag::Strikethrough) | Event::End(TagEnd::Strikethrough) => {
+                markdown.push_str("~~")
+            }
+            Event::Start(Tag::Link {
+                dest_url, title, ..
+            }) => {
+                markdown.push('[');
+                links.push((dest_url.clone(), title.clone()));
+            }
+            Event::Start(Tag::Image {
+                dest_url, title, ..
+            }) => {
+                markdown.push_str("![");
+                links.push((dest_url.clone(), title.clone()));
+            }
+            Event::End(TagEnd::Link) | Event::End(TagEnd::Image) => {
+                if let Some((dest_url, title)) = links.pop() {
+                    match title.is_empty() {
+                        true => markdown.push_str(&format!("]({})", dest_url)),
+                        false => markdown.push_str(&format!(
+                            "]({} \"{}\")",
+                            dest_url,
+                            title.replace('"', "\\\"")
+                        )),
+                    }
+                }
+            }
+            Event::Text(text) | Event::Html(text) | Event::InlineHtml(text) => {
+                markdown.push_str(text)
+            }
+            Event::Code(code) => {
+                let fence = "`".repeat(backtick_run(code) + 1);
+                let padding = match code.starts_with('`') || code.ends_with('`') {
+                    true => " ",
+                    false => "",
+                };
+                markdown.push_str(&format!("{}{}{}{}{}", fence, padding, code, padding, fence));
+            }
+            Event::SoftBreak => markdown.push('\n'),
+            Event::HardBreak => markdown.push_str("\\\n"),
+            Event::Rule => {
+                start_block(&mut markdown);
+                markdown.push_str("---\n");
+            }
+            _ => {}
+        }
+    }
+
+    markdown
+}
+
+#[cfg(test)]
+mod test {
+    use anyhow::Result;
+    use pulldown_cmark::{html::push_html, Parser};
+    use test_log::test;
+
+    use super::*;
+    use crate::markdown::parse_blocks;
+
+    fn html(markdown: &str) -> String {
+        let mut html = String::new();
+        push_html(&mut html, Parser::new(markdown));
+        html
+    }
+
+    #[test]
+    fn test_events_to_markdown_blocks() -> Result<()> {
+        let content = "\
+        {{#tab }}\n\
+        Some content.\n\
+        {{#endtab }}\n\
+        {{#tab }}\n\
+        \n\
+        ```rust\n\
+        let a = 1 + 2;\n\
+        ```\n\
+        \n\
+        {{#endtab }}\n\
+        ";
+
+        let blocks = parse_blocks(
+            content,
+            |event| matches!(event, Event::Text(text) if text.starts_with("{{#tab ")),
+            |event| matches!(event, Event::Text(text) if text.starts_with("{{#endtab ")),
+        )?;
+        let actual = blocks
+            .iter()
+            .map(|block| {
+                let events = block
+                    .events
+                    .iter()
+                    .map(|(event, _)| event.clone())
+                    .collect::<Vec<_>>();
+                events_to_markdown(&events)
+            })
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            vec![
+                "{{#tab }}\nSome content.\n{{#endtab }}",
+                "{{#tab }}\n\n```rust\nlet a = 1 + 2;\n```\n{{#endtab }}",
+            ],
+            actual
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_events_to_markdown_round_trip() {
+        let markdown = "\
+        ## Install `example`\n\
+        \n\
+        Run *this* **command**\\\n\
+        with ``a ` tick`` and [a link](https://example.com \"Example\").\n\
+        \n\
+        ````shell\n\
+        ```\n\
+        ````\n\
+        \n\
+        ---\n\
+        \n\
+        \x20   indented\n\
+        \x20   code\n";
+
+        let events = Parser::new(markdown).collect::<Vec<_>>();
+        let actual = events_to_markdown(&events);
+
+        assert_eq!(html(markdown), html(&actual));
+        assert_eq!(markdown, actual);
+    }
+}
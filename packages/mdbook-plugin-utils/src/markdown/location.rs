@@ -0,0 +1,98 @@
+use std::ops::Range;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Position {
+    pub line: usize,
+    pub column: usize,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Location {
+    pub start: Position,
+    pub end: Position,
+}
+
+pub fn position(source: &str, offset: usize) -> Position {
+    let mut offset = offset.min(source.len());
+    while !source.is_char_boundary(offset) {
+        offset -= 1;
+    }
+
+    let prefix = &source[..offset];
+    let line_start = prefix.rfind('\n').map_or(0, |index| index + 1);
+    let mut column = prefix[line_start..].chars().count() + 1;
+
+    if prefix.ends_with('\r') && source[offset..].starts_with('\n') {
+        column -= 1;
+    }
+
+    Position {
+        line: prefix.matches('\n').count() + 1,
+        column,
+    }
+}
+
+pub fn location(source: &str, span: &Range<usize>) -> Location {
+    Location {
+        start: position(source, span.start),
+        end: position(source, span.end),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use test_log::test;
+
+    use super::*;
+
+    #[test]
+    fn test_location() {
+        let source = "first line\nsecond line\nthird line";
+
+        assert_eq!(
+            Location {
+                start: Position { line: 1, column: 1 },
+                end: Position { line: 1, column: 6 },
+            },
+            location(source, &(0..5))
+        );
+        assert_eq!(
+            Location {
+                start: Position { line: 2, column: 8 },
+                end: Position { line: 3, column: 6 },
+            },
+            location(source, &(18..28))
+        );
+    }
+
+    #[test]
+    fn test_location_crlf() {
+        let source = "first line\r\nsecond line\r\nthird line";
+
+        assert_eq!(
+            Position {
+                line: 1,
+                column: 11
+            },
+            position(source, 10)
+        );
+        assert_eq!(
+            Position {
+                line: 1,
+                column: 11
+            },
+            position(source, 11)
+        );
+        assert_eq!(Position { line: 2, column: 1 }, position(source, 12));
+        assert_eq!(Position { line: 3, column: 1 }, position(source, 25));
+    }
+
+    #[test]
+    fn test_location_multi_byte() {
+        let source = "é\nçà ✓";
+
+        assert_eq!(Position { line: 2, column: 3 }, position(source, 7));
+        assert_eq!(Position { line: 2, column: 2 }, position(source, 6));
+        assert_eq!(Position { line: 2, column: 5 }, position(source, 100));
+    }
+}
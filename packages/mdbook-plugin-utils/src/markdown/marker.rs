@@ -0,0 +1,336 @@
+use std::{collections::BTreeMap, ops::Range};
+
+use crate::markdown::error::MarkerParseError;
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct MarkerAttributes {
+    pub name: String,
+    pub attributes: BTreeMap<String, Option<String>>,
+}
+
+impl MarkerAttributes {
+    pub fn parse(marker: &str) -> Result<Self, MarkerParseError> {
+        let (parser, name) = marker_parser(marker)?;
+
+        Self::parse_with(parser, name)
+    }
+
+    // Parses attributes without the marker around them, e.g. split by a plugin with its own marker
+    // syntax. Spans of errors are in the attributes.
+    pub fn from_attributes(name: &str, attributes: &str) -> Result<Self, MarkerParseError> {
+        let parser = AttributeParser {
+            source: attributes,
+            offset: 0,
+            end: attributes.len(),
+        };
+
+        Self::parse_with(parser, name)
+    }
+
+    fn parse_with(mut parser: AttributeParser<'_>, name: &str) -> Result<Self, MarkerParseError> {
+        let mut attributes = BTreeMap::new();
+
+        loop {
+            parser.take_while(|c| c.is_whitespace() || c == ',');
+            if parser.is_empty() {
+                break;
+            }
+
+            let key_start = parser.offset;
+            let key = parser.take_while(|c| c.is_alphanumeric() || c == '-' || c == '_');
+            let key_span = key_start..parser.offset;
+            if key.is_empty() {
+                return Err(MarkerParseError::UnexpectedCharacter {
+                    span: parser.next_char_span(),
+                });
+            }
+
+            let value = match parser.peek() {
+                Some('=') => {
+                    parser.offset += 1;
+                    Some(parser.quoted_value()?)
+                }
+                Some(c) if !c.is_whitespace() && c != ',' => {
+                    return Err(MarkerParseError::UnexpectedCharacter {
+                        span: parser.next_char_span(),
+                    });
+                }
+                _ => None,
+            };
+
+            if attributes.insert(key.to_string(), value).is_some() {
+                return Err(MarkerParseError::DuplicateAttribute { span: key_span });
+            }
+        }
+
+        Ok(MarkerAttributes {
+            name: name.to_string(),
+            attributes,
+        })
+    }
+
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.attributes.get(key).and_then(|value| value.as_deref())
+    }
+
+    pub fn has(&self, key: &str) -> bool {
+        self.attributes.contains_key(key)
+    }
+}
+
+// Parser for the attributes of a `{{#name attributes}}` marker, and the name of the marker.
+fn marker_parser(marker: &str) -> Result<(AttributeParser<'_>, &str), MarkerParseError> {
+    let start = marker.len() - marker.trim_start().len();
+    let end = marker.trim_end().len();
+
+    if end < start + 5 || !marker[start..end].starts_with("{{#") || !marker[..end].ends_with("}}") {
+        return Err(MarkerParseError::MissingDelimiters { span: start..end });
+    }
+
+    let mut parser = AttributeParser {
+        source: marker,
+        offset: start + 3,
+        end: end - 2,
+    };
+
+    let name = parser.take_while(|c| !c.is_whitespace());
+    if name.is_empty() {
+        return Err(MarkerParseError::MissingName { span: start..end });
+    }
+
+    Ok((parser, name))
+}
+
+// Splits a marker into its name and its attributes as written, e.g. for plugins with their own
+// attribute syntax. Use `MarkerAttributes` to parse the attributes.
+pub fn split_marker(marker: &str) -> Result<(&str, &str), MarkerParseError> {
+    let (parser, name) = marker_parser(marker)?;
+
+    Ok((name, marker[parser.offset..parser.end].trim()))
+}
+
+// Splits a marker into its name and attributes like `split_marker`, but allows whitespace around
+// the `#` and the name, e.g. `{{# tabs }}`. Closing markers like `{{/tabs}}` keep the `/` in their
+// name. Text that is not a single marker is `None`.
+pub fn parse_marker(text: &str) -> Option<(&str, &str)> {
+    let inner = text
+        .trim()
+        .strip_prefix("{{")?
+        .strip_suffix("}}")?
+        .trim_start();
+    let inner = match inner.strip_prefix('#') {
+        Some(inner) => inner.trim_start(),
+        None if inner.starts_with('/') => inner,
+        None => return None,
+    };
+
+    // Multiple markers on one line, e.g. `{{#tabs }}{{#endtabs }}`, are not a single marker.
+    if inner.contains("{{") || inner.contains("}}") {
+        return None;
+    }
+
+    let name_end = inner.find(char::is_whitespace).unwrap_or(inner.len());
+
+    Some((&inner[..name_end], inner[name_end..].trim()))
+}
+
+struct AttributeParser<'a> {
+    source: &'a str,
+    offset: usize,
+    end: usize,
+}
+
+impl<'a> AttributeParser<'a> {
+    fn is_empty(&self) -> bool {
+        self.offset >= self.end
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.source[self.offset..self.end].chars().next()
+    }
+
+    fn next_char_span(&self) -> Range<usize> {
+        self.offset..self.offset + self.peek().map_or(0, char::len_utf8)
+    }
+
+    fn take_while<P>(&mut self, predicate: P) -> &'a str
+    where
+        P: Fn(char) -> bool,
+    {
+        let start = self.offset;
+
+        while let Some(c) = self.peek() {
+            if !predicate(c) {
+                break;
+            }
+            self.offset += c.len_utf8();
+        }
+
+        &self.source[start..self.offset]
+    }
+
+    // Values are quoted, except for the `true` and `false` literals of flags, e.g. `raw=true`.
+    fn quoted_value(&mut self) -> Result<String, MarkerParseError> {
+        let start = self.offset;
+
+        if self.peek() != Some('"') {
+            let value = self.take_while(|c| !c.is_whitespace() && c != ',');
+            if value == "true" || value == "false" {
+                return Ok(value.to_string());
+            }
+
+            return Err(MarkerParseError::MissingQuotes {
+                span: start..self.offset,
+            });
+        }
+        self.offset += 1;
+
+        let mut value = String::new();
+        let mut escaped = false;
+
+        while let Some(c) = self.peek() {
+            self.offset += c.len_utf8();
+
+            match c {
+                _ if escaped => {
+                    value.push(c);
+                    escaped = false;
+                }
+                '\\' => escaped = true,
+                '"' => {
+                    return match self.peek() {
+                        Some(c) if !c.is_whitespace() && c != ',' => {
+                            Err(MarkerParseError::UnexpectedCharacter {
+                                span: self.next_char_span(),
+                            })
+                        }
+                        _ => Ok(value),
+                    };
+                }
+                _ => value.push(c),
+            }
+        }
+
+        Err(MarkerParseError::UnclosedQuote {
+            span: start..self.end,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use test_log::test;
+
+    use super::*;
+
+    #[test]
+    fn test_parse_marker_attributes() -> Result<(), MarkerParseError> {
+        let actual = MarkerAttributes::parse(
+            "{{#tabs global=\"example\" default=\"Say \\\"hi\\\"\" remember}}",
+        )?;
+
+        assert_eq!("tabs", actual.name);
+        assert_eq!(3, actual.attributes.len());
+        assert_eq!(Some("example"), actual.get("global"));
+        assert_eq!(Some("Say \"hi\""), actual.get("default"));
+        assert_eq!(None, actual.get("remember"));
+        assert!(actual.has("remember"));
+        assert!(!actual.has("sync"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_marker_attributes_empty() -> Result<(), MarkerParseError> {
+        let expected = MarkerAttributes {
+            name: "tabs".to_string(),
+            attributes: BTreeMap::new(),
+        };
+
+        assert_eq!(expected, MarkerAttributes::parse("{{#tabs}}")?);
+        assert_eq!(expected, MarkerAttributes::parse("  {{#tabs }}\n")?);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_marker_attributes_from_attributes() -> Result<(), MarkerParseError> {
+        let actual = MarkerAttributes::from_attributes(
+            "tab",
+            "name=\"Say \\\"hi\\\"\", raw=true hidden=false icon",
+        )?;
+
+        assert_eq!("tab", actual.name);
+        assert_eq!(Some("Say \"hi\""), actual.get("name"));
+        assert_eq!(Some("true"), actual.get("raw"));
+        assert_eq!(Some("false"), actual.get("hidden"));
+        assert!(actual.has("icon"));
+        assert_eq!(
+            Err(MarkerParseError::MissingQuotes { span: 5..10 }),
+            MarkerAttributes::from_attributes("tab", "name=Linux")
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_split_marker() -> Result<(), MarkerParseError> {
+        assert_eq!(
+            ("tabs", "global=\"example\", remember"),
+            split_marker("  {{#tabs  global=\"example\", remember }}\n")?
+        );
+        assert_eq!(("tabs", ""), split_marker("{{#tabs}}")?);
+        assert_eq!(
+            Err(MarkerParseError::MissingName { span: 0..6 }),
+            split_marker("{{# }}")
+        );
+        assert!(split_marker("Text.").is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_marker() {
+        assert_eq!(Some(("tabs", "")), parse_marker("{{#tabs}}"));
+        assert_eq!(Some(("tabs", "")), parse_marker("{{# tabs }}"));
+        assert_eq!(Some(("tabs", "")), parse_marker("{{#tabs  }}"));
+        assert_eq!(Some(("tabs", "")), parse_marker("{{ #\ttabs\t}}"));
+        assert_eq!(
+            Some(("tab", "name=\"Linux\"")),
+            parse_marker("{{#tab\tname=\"Linux\"  }}")
+        );
+        assert_eq!(Some(("endtabs", "")), parse_marker(" {{# endtabs}} "));
+
+        assert_eq!(None, parse_marker("{{tabs}}"));
+        assert_eq!(None, parse_marker("{{#tabs }} text"));
+        assert_eq!(None, parse_marker("{{#tabs }}{{#endtabs }}"));
+    }
+
+    #[test]
+    fn test_parse_marker_attributes_errors() {
+        assert_eq!(
+            Err(MarkerParseError::MissingQuotes { span: 12..17 }),
+            MarkerAttributes::parse("{{#tab name=Linux}}")
+        );
+        assert_eq!(
+            Err(MarkerParseError::UnclosedQuote { span: 12..18 }),
+            MarkerAttributes::parse("{{#tab name=\"Linux}}")
+        );
+        assert_eq!(
+            Err(MarkerParseError::UnexpectedCharacter { span: 19..20 }),
+            MarkerAttributes::parse("{{#tab name=\"Linux\"x}}")
+        );
+        assert_eq!(
+            Err(MarkerParseError::DuplicateAttribute { span: 20..24 }),
+            MarkerAttributes::parse("{{#tab name=\"Linux\" name=\"macOS\"}}")
+        );
+        assert_eq!(
+            Err(MarkerParseError::MissingName { span: 0..6 }),
+            MarkerAttributes::parse("{{# }}")
+        );
+        assert_eq!(
+            Err(MarkerParseError::MissingDelimiters { span: 0..7 }),
+            MarkerAttributes::parse("{{#tabs ")
+        );
+    }
+}
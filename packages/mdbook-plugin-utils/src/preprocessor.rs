@@ -0,0 +1,157 @@
+use std::io::{self, Read};
+#[cfg(feature = "cli")]
+use std::{fs::File, path::PathBuf};
+
+#[cfg(feature = "cli")]
+use anyhow::Context;
+use anyhow::{anyhow, bail, Result};
+#[cfg(feature = "cli")]
+use clap::{Args, FromArgMatches, Parser, Subcommand};
+use log::warn;
+use mdbook::preprocess::{CmdPreprocessor, Preprocessor};
+use semver::{Version, VersionReq};
+
+#[cfg(feature = "cli")]
+#[derive(Parser)]
+struct Cli<C: Subcommand> {
+    #[arg(long)]
+    strict_version: bool,
+
+    // Reads the input from a file instead of stdin, e.g. a saved input to debug the plugin.
+    #[arg(long, hide = true, value_name = "PATH")]
+    from_file: Option<PathBuf>,
+
+    #[command(subcommand)]
+    command: Option<Commands<C>>,
+}
+
+#[cfg(feature = "cli")]
+#[derive(Subcommand)]
+enum Commands<C: Subcommand> {
+    Supports(SupportsArgs),
+    #[command(flatten)]
+    Plugin(C),
+}
+
+#[cfg(feature = "cli")]
+#[derive(Args)]
+struct SupportsArgs {
+    renderer: String,
+}
+
+// The command is usually `clap::command!()`, so it has the name and version of the plugin binary.
+#[cfg(feature = "cli")]
+pub fn run_preprocessor_cli<C, F>(
+    command: clap::Command,
+    preprocessor: &dyn Preprocessor,
+    handle_command: F,
+) -> Result<()>
+where
+    C: Subcommand,
+    F: FnOnce(C) -> Result<()>,
+{
+    let matches = Cli::<C>::augment_args(command.propagate_version(true)).get_matches();
+    let cli = Cli::<C>::from_arg_matches(&matches)?;
+
+    match cli.command {
+        Some(Commands::Supports(SupportsArgs { renderer })) => {
+            handle_supports(preprocessor, &renderer)
+        }
+        Some(Commands::Plugin(command)) => handle_command(command),
+        None => match cli.from_file {
+            Some(path) => handle_preprocessing(
+                preprocessor,
+                File::open(&path)
+                    .with_context(|| format!("Failed to open `{}`.", path.display()))?,
+                cli.strict_version,
+            ),
+            None => handle_preprocessing(preprocessor, io::stdin(), cli.strict_version),
+        },
+    }
+}
+
+pub fn handle_supports(preprocessor: &dyn Preprocessor, renderer: &str) -> Result<()> {
+    match preprocessor.supports_renderer(renderer) {
+        true => Ok(()),
+        false => Err(anyhow!("Renderer `{renderer}` is not supported.")),
+    }
+}
+
+pub fn check_version(
+    preprocessor: &dyn Preprocessor,
+    mdbook_version: &str,
+    strict: bool,
+) -> Result<()> {
+    let book_version = Version::parse(mdbook_version)?;
+    let version_req = VersionReq::parse(mdbook::MDBOOK_VERSION)?;
+
+    if !version_req.matches(&book_version) {
+        let message = format!(
+            "The {} plugin was built against version {} of mdbook, but we're being called from version {}",
+            preprocessor.name(),
+            mdbook::MDBOOK_VERSION,
+            mdbook_version
+        );
+
+        match strict {
+            true => bail!(message),
+            false => warn!("{}", message),
+        }
+    }
+
+    Ok(())
+}
+
+pub fn handle_preprocessing<R: Read>(
+    preprocessor: &dyn Preprocessor,
+    reader: R,
+    strict_version: bool,
+) -> Result<()> {
+    let (ctx, book) = CmdPreprocessor::parse_input(reader)?;
+
+    check_version(preprocessor, &ctx.mdbook_version, strict_version)?;
+
+    let processed_book = preprocessor.run(&ctx, book)?;
+    serde_json::to_writer(io::stdout(), &processed_book)?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use mdbook::{book::Book, preprocess::PreprocessorContext};
+    use test_log::test;
+
+    use super::*;
+
+    struct TestPreprocessor;
+
+    impl Preprocessor for TestPreprocessor {
+        fn name(&self) -> &str {
+            "test"
+        }
+
+        fn run(&self, _ctx: &PreprocessorContext, book: Book) -> Result<Book> {
+            Ok(book)
+        }
+    }
+
+    #[test]
+    fn test_check_version() -> Result<()> {
+        check_version(&TestPreprocessor, mdbook::MDBOOK_VERSION, true)?;
+        check_version(&TestPreprocessor, "0.1.0", false)?;
+
+        assert_eq!(
+            format!(
+                "The test plugin was built against version {} of mdbook, but we're being called from version 0.1.0",
+                mdbook::MDBOOK_VERSION
+            ),
+            check_version(&TestPreprocessor, "0.1.0", true)
+                .unwrap_err()
+                .to_string()
+        );
+        assert!(check_version(&TestPreprocessor, "invalid", false).is_err());
+
+        Ok(())
+    }
+}
@@ -0,0 +1,84 @@
+// Measures `TabsPreprocessor::run` over a generated book of 500 chapters with tabs. Run with
+// `cargo bench -p mdbook-tabs`.
+
+use std::env;
+
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion};
+use mdbook::{
+    book::{Book, Chapter},
+    preprocess::{Preprocessor, PreprocessorContext},
+};
+use mdbook_tabs::TabsPreprocessor;
+
+const CHAPTERS: usize = 500;
+
+fn chapter(index: usize) -> Chapter {
+    let tabs = "\
+        {{#tabs global=\"os\" }}\n\
+        {{#tab name=\"Linux\" }}\n\
+        Install with `apt`:\n\
+        \n\
+        ```shell\n\
+        apt install example\n\
+        ```\n\
+        {{#endtab }}\n\
+        {{#tab name=\"macOS\" }}\n\
+        {{#tabs }}\n\
+        {{#tab name=\"Homebrew\" }}\n\
+        brew install example\n\
+        {{#endtab }}\n\
+        {{#tab name=\"MacPorts\" }}\n\
+        port install example\n\
+        {{#endtab }}\n\
+        {{#endtabs }}\n\
+        {{#endtab }}\n\
+        {{#endtabs }}\n";
+
+    let content = format!(
+        "# Chapter {}\n\nSome text with `{{{{#include example.md}}}}` escaped as \\{{{{#tabs }}}}.\n\n{}",
+        index,
+        format!("{}\nMore text between the tabs.\n\n", tabs).repeat(5)
+    );
+
+    Chapter::new(
+        &format!("Chapter {}", index),
+        content,
+        format!("chapter-{}.md", index),
+        vec![],
+    )
+}
+
+fn book() -> Book {
+    let mut book = Book::new();
+    for index in 0..CHAPTERS {
+        book.push_item(chapter(index));
+    }
+    book
+}
+
+fn bench_run(c: &mut Criterion) {
+    let ctx: PreprocessorContext = serde_json::from_value(serde_json::json!({
+        "root": env::temp_dir(),
+        "config": {},
+        "renderer": "html",
+        "mdbook_version": mdbook::MDBOOK_VERSION,
+    }))
+    .expect("context should be valid");
+    let book = book();
+    let preprocessor = TabsPreprocessor::new();
+
+    c.bench_function("run 500 chapters", |b| {
+        b.iter_batched(
+            || book.clone(),
+            |book| {
+                preprocessor
+                    .run(&ctx, book)
+                    .expect("tabs should be processed")
+            },
+            BatchSize::LargeInput,
+        )
+    });
+}
+
+criterion_group!(benches, bench_run);
+criterion_main!(benches);
@@ -1,35 +1,23 @@
-use std::{
-    env, fs,
-    io::{self, Read},
-};
-
-use anyhow::{anyhow, Result};
-use clap::{Args, Parser, Subcommand};
-use log::warn;
-use mdbook::{
-    preprocess::{CmdPreprocessor, Preprocessor},
-    MDBook,
-};
-use mdbook_tabs::TabsPreprocessor;
-use semver::{Version, VersionReq};
-
-#[derive(Parser)]
-#[command(version, about, long_about = None)]
-#[command(propagate_version = true)]
-struct Cli {
-    #[command(subcommand)]
-    command: Option<Commands>,
-}
+use std::{env, fs};
+
+use anyhow::{bail, Result};
+use clap::{command, Args, Subcommand};
+use log::{error, info, warn};
+use mdbook::{Config, MDBook};
+use mdbook_plugin_utils::preprocessor::run_preprocessor_cli;
+use mdbook_tabs::{css, js, PreprocessorConfig, TabsPreprocessor};
 
 #[derive(Subcommand)]
 enum Commands {
+    Init(InitArgs),
     Install,
-    Supports(SupportsArgs),
+    Validate,
 }
 
 #[derive(Args)]
-struct SupportsArgs {
-    renderer: String,
+struct InitArgs {
+    #[arg(long)]
+    write: bool,
 }
 
 fn main() -> Result<()> {
@@ -39,62 +27,128 @@ fn main() -> Result<()> {
     }
     logger.init();
 
-    let cli = Cli::parse();
     let preprocessor = TabsPreprocessor::new();
 
-    match &cli.command {
-        Some(subcommand) => match subcommand {
-            Commands::Install => handle_install(),
-            Commands::Supports(args) => handle_supports(&preprocessor, args),
-        },
-        None => handle_preprocessing(&preprocessor, io::stdin()),
+    run_preprocessor_cli(command!(), &preprocessor, |command| match command {
+        Commands::Init(args) => handle_init(&args),
+        Commands::Install => handle_install(),
+        Commands::Validate => handle_validate(&preprocessor),
+    })
+}
+
+fn init_stanzas(book_config: &Config) -> Result<Vec<String>> {
+    let config = PreprocessorConfig::from_config(book_config)?;
+    let mut stanzas = vec![];
+
+    if book_config.get("preprocessor.tabs").is_none() {
+        stanzas.push("[preprocessor.tabs]\n".to_string());
+    }
+
+    if config.inline_assets {
+        return Ok(stanzas);
+    }
+
+    let assets = [
+        ("additional-css", "theme/tabs.css"),
+        ("additional-js", "theme/tabs.js"),
+    ];
+
+    if book_config.get("output.html").is_none() {
+        stanzas.push(format!(
+            "[output.html]\n{}",
+            assets
+                .iter()
+                .map(|(key, path)| format!("{} = [\"{}\"]\n", key, path))
+                .collect::<String>()
+        ));
+
+        return Ok(stanzas);
     }
+
+    for (key, path) in assets {
+        let exists = book_config
+            .get(&format!("output.html.{}", key))
+            .and_then(|value| value.as_array())
+            .is_some_and(|values| {
+                values
+                    .iter()
+                    .any(|value| value.as_str().is_some_and(|value| value.ends_with(path)))
+            });
+
+        if !exists {
+            warn!(
+                "Add `\"{}\"` to `{}` in the existing `[output.html]` section.",
+                path, key
+            );
+        }
+    }
+
+    Ok(stanzas)
+}
+
+fn handle_init(InitArgs { write }: &InitArgs) -> Result<()> {
+    let path = env::current_dir()?.join("book.toml");
+
+    let content = match path.exists() {
+        true => fs::read_to_string(&path)?,
+        false if *write => bail!("File `{}` does not exist.", path.display()),
+        false => String::new(),
+    };
+    let stanzas = init_stanzas(&content.parse::<Config>()?)?;
+
+    if !*write {
+        print!("{}", stanzas.join("\n"));
+        return Ok(());
+    }
+
+    if stanzas.is_empty() {
+        info!("No sections to add to `{}`.", path.display());
+        return Ok(());
+    }
+
+    let mut content = content;
+    if !content.is_empty() {
+        content.push_str(match content.ends_with('\n') {
+            true => "\n",
+            false => "\n\n",
+        });
+    }
+    content.push_str(&stanzas.join("\n"));
+
+    fs::write(&path, content)?;
+    info!(
+        "Updated `{}`, run `mdbook-tabs install` to add the CSS and JS files.",
+        path.display()
+    );
+
+    Ok(())
 }
 
 fn handle_install() -> Result<()> {
     let book = MDBook::load(env::current_dir()?)?;
+    let config = PreprocessorConfig::from_config(&book.config)?;
     let directory = book.root.join("theme");
 
     if !directory.exists() {
         fs::create_dir(&directory)?;
     }
 
-    let css_content = include_str!("../theme/tabs.css");
-    let js_content = include_str!("../theme/tabs.js");
-
-    fs::write(directory.join("tabs.css"), css_content)?;
-    fs::write(directory.join("tabs.js"), js_content)?;
+    fs::write(directory.join("tabs.css"), css(&config))?;
+    fs::write(directory.join("tabs.js"), js(&config))?;
 
     Ok(())
 }
 
-fn handle_supports(
-    preprocessor: &dyn Preprocessor,
-    SupportsArgs { renderer }: &SupportsArgs,
-) -> Result<()> {
-    match preprocessor.supports_renderer(renderer) {
-        true => Ok(()),
-        false => Err(anyhow!("Renderer `{renderer}` is not supported.")),
-    }
-}
-
-fn handle_preprocessing<R: Read>(preprocessor: &dyn Preprocessor, reader: R) -> Result<()> {
-    let (ctx, book) = CmdPreprocessor::parse_input(reader)?;
-
-    let book_version = Version::parse(&ctx.mdbook_version)?;
-    let version_req = VersionReq::parse(mdbook::MDBOOK_VERSION)?;
+fn handle_validate(preprocessor: &TabsPreprocessor) -> Result<()> {
+    let book = MDBook::load(env::current_dir()?)?;
 
-    if !version_req.matches(&book_version) {
-        warn!(
-            "The {} plugin was built against version {} of mdbook, but we're being called from version {}",
-            preprocessor.name(),
-            mdbook::MDBOOK_VERSION,
-            ctx.mdbook_version
-        );
+    let problems = preprocessor.validate_book(&book)?;
+    for problem in &problems {
+        error!("{}", problem);
     }
 
-    let processed_book = preprocessor.run(&ctx, book)?;
-    serde_json::to_writer(io::stdout(), &processed_book)?;
-
-    Ok(())
+    match problems.len() {
+        0 => Ok(()),
+        count => bail!("Found {} problem(s) in tabs.", count),
+    }
 }
@@ -0,0 +1,263 @@
+use anyhow::Result;
+
+use crate::{
+    config::{
+        Delimiter, IdStrategy, Orientation, PreprocessorConfig, RenderMode, Responsive, Syntax,
+    },
+    preprocessor::TabsPreprocessor,
+};
+
+#[derive(Clone, Debug, Default)]
+pub struct TabsPreprocessorBuilder {
+    config: PreprocessorConfig,
+}
+
+impl TabsPreprocessorBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn keyword(mut self, keyword: impl Into<String>) -> Self {
+        self.config.keyword = keyword.into();
+        self
+    }
+
+    pub fn syntax(mut self, syntax: Syntax) -> Self {
+        self.config.syntax = syntax;
+        self
+    }
+
+    pub fn remember(mut self, remember: bool) -> Self {
+        self.config.remember = remember;
+        self
+    }
+
+    pub fn sync(mut self, sync: bool) -> Self {
+        self.config.sync = sync;
+        self
+    }
+
+    pub fn update_hash(mut self, update_hash: bool) -> Self {
+        self.config.update_hash = update_hash;
+        self
+    }
+
+    pub fn class_prefix(mut self, class_prefix: impl Into<String>) -> Self {
+        self.config.class_prefix = class_prefix.into();
+        self
+    }
+
+    pub fn inline_assets(mut self, inline_assets: bool) -> Self {
+        self.config.inline_assets = inline_assets;
+        self
+    }
+
+    pub fn heading_level(mut self, heading_level: usize) -> Self {
+        self.config.heading_level = Some(heading_level);
+        self
+    }
+
+    pub fn collapse_single(mut self, collapse_single: bool) -> Self {
+        self.config.collapse_single = collapse_single;
+        self
+    }
+
+    pub fn responsive(mut self, responsive: Responsive) -> Self {
+        self.config.responsive = Some(responsive);
+        self
+    }
+
+    pub fn orientation(mut self, orientation: Orientation) -> Self {
+        self.config.orientation = orientation;
+        self
+    }
+
+    pub fn static_select(mut self, static_select: impl Into<String>) -> Self {
+        self.config.static_select = Some(static_select.into());
+        self
+    }
+
+    pub fn max_visible_tabs(mut self, max_visible_tabs: usize) -> Self {
+        self.config.max_visible_tabs = Some(max_visible_tabs);
+        self
+    }
+
+    pub fn delimiter(mut self, delimiter: Delimiter) -> Self {
+        self.config.delimiter = delimiter;
+        self
+    }
+
+    pub fn badge(mut self, badge: bool) -> Self {
+        self.config.badge = badge;
+        self
+    }
+
+    pub fn wrapper_tag(mut self, wrapper_tag: impl Into<String>) -> Self {
+        self.config.wrapper_tag = wrapper_tag.into();
+        self
+    }
+
+    pub fn wrapper_classes<I, S>(mut self, wrapper_classes: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.config.wrapper_classes = wrapper_classes.into_iter().map(Into::into).collect();
+        self
+    }
+
+    pub fn render_mode(mut self, render_mode: RenderMode) -> Self {
+        self.config.render_mode = render_mode;
+        self
+    }
+
+    pub fn breakpoint(mut self, breakpoint: usize) -> Self {
+        self.config.breakpoint = breakpoint;
+        self
+    }
+
+    pub fn max_blocks(mut self, max_blocks: usize) -> Self {
+        self.config.max_blocks = Some(max_blocks);
+        self
+    }
+
+    pub fn max_block_size(mut self, max_block_size: usize) -> Self {
+        self.config.max_block_size = Some(max_block_size);
+        self
+    }
+
+    pub fn pretty(mut self, pretty: bool) -> Self {
+        self.config.pretty = pretty;
+        self
+    }
+
+    pub fn empty_placeholder(mut self, empty_placeholder: impl Into<String>) -> Self {
+        self.config.empty_placeholder = Some(empty_placeholder.into());
+        self
+    }
+
+    pub fn debug_comments(mut self, debug_comments: bool) -> Self {
+        self.config.debug_comments = debug_comments;
+        self
+    }
+
+    pub fn lazy(mut self, lazy: bool) -> Self {
+        self.config.lazy = lazy;
+        self
+    }
+
+    pub fn id_strategy(mut self, id_strategy: IdStrategy) -> Self {
+        self.config.id_strategy = id_strategy;
+        self
+    }
+
+    pub fn cache(mut self, cache: bool) -> Self {
+        self.config.cache = cache;
+        self
+    }
+
+    pub fn disabled_chapters<I, S>(mut self, paths: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.config.disabled_chapters = paths.into_iter().map(Into::into).collect();
+        self
+    }
+
+    pub fn trim_trailing_lines(mut self, trim_trailing_lines: bool) -> Self {
+        self.config.trim_trailing_lines = trim_trailing_lines;
+        self
+    }
+
+    pub fn end_markers<I, S>(mut self, end_markers: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.config.end_markers = Some(end_markers.into_iter().map(Into::into).collect());
+        self
+    }
+
+    pub fn active_class(mut self, active_class: impl Into<String>) -> Self {
+        self.config.active_class = active_class.into();
+        self
+    }
+
+    pub fn hidden_class(mut self, hidden_class: impl Into<String>) -> Self {
+        self.config.hidden_class = hidden_class.into();
+        self
+    }
+
+    pub fn no_js_fallback(mut self, no_js_fallback: bool) -> Self {
+        self.config.no_js_fallback = no_js_fallback;
+        self
+    }
+
+    pub fn renderers<I, S>(mut self, renderers: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.config.renderers = renderers.into_iter().map(Into::into).collect();
+        self
+    }
+
+    pub fn build(self) -> Result<TabsPreprocessor> {
+        let mut config = self.config;
+        config.validate()?;
+
+        Ok(TabsPreprocessor::with_config(config))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use mdbook::preprocess::Preprocessor;
+    use test_log::test;
+
+    use super::*;
+
+    #[test]
+    fn test_builder() -> Result<()> {
+        let preprocessor = TabsPreprocessor::builder()
+            .keyword("tabset")
+            .heading_level(4)
+            .sync(true)
+            .build()?;
+
+        let actual = preprocessor.render_block(
+            "{{#tabset }}\n\n#### Linux\n\nLinux.\n\n#### macOS\n\nmacOS.\n\n{{#endtabset }}",
+        )?;
+
+        assert!(actual.contains("data-tabsync=\"[&quot;Linux&quot;,&quot;macOS&quot;]\""));
+        assert!(actual.contains("id=\"mdbook-tab-0-macos\""));
+        assert_eq!("tabs", preprocessor.name());
+
+        assert!(TabsPreprocessor::builder()
+            .keyword("tab set!")
+            .build()
+            .is_err());
+        assert!(TabsPreprocessor::builder()
+            .wrapper_tag("script")
+            .build()
+            .is_err());
+        assert!(TabsPreprocessor::builder()
+            .wrapper_classes(["not-prose", "my-4\" onclick=\""])
+            .build()
+            .is_err());
+
+        let actual = TabsPreprocessor::builder()
+            .wrapper_tag("figure")
+            .wrapper_classes(["not-prose"])
+            .render_mode(RenderMode::Html)
+            .build()?
+            .render_block(
+                "{{#tabs }}\n{{#tab name=\"Linux\" }}\nLinux.\n{{#endtab }}\n{{#endtabs }}",
+            )?;
+        assert!(actual.starts_with("<figure class=\"mdbook-tabs-container not-prose\">"));
+        assert!(actual.contains("<p>Linux.</p>"));
+
+        Ok(())
+    }
+}
@@ -0,0 +1,122 @@
+use std::{fs, path::PathBuf};
+
+use anyhow::Result;
+use log::warn;
+use serde::{Deserialize, Serialize};
+
+use crate::config::PreprocessorConfig;
+
+pub const CACHE_DIRECTORY: &str = ".mdbook-tabs-cache";
+
+#[derive(Deserialize, Serialize)]
+struct Entry {
+    key: String,
+    path: String,
+    content: String,
+    output: String,
+    groups: usize,
+}
+
+pub struct Cache {
+    directory: PathBuf,
+    key: String,
+}
+
+impl Cache {
+    pub fn new(directory: PathBuf, config: &PreprocessorConfig) -> Result<Self> {
+        fs::create_dir_all(&directory)?;
+
+        // The renderer is not serialized with the config, but changes the output.
+        let key = format!(
+            "{}\n{}\n{}",
+            env!("CARGO_PKG_VERSION"),
+            config.renderer,
+            serde_json::to_string(config)?
+        );
+
+        Ok(Cache { directory, key })
+    }
+
+    fn entry_path(&self, path: &str, content: &str) -> PathBuf {
+        let hash = [self.key.as_str(), path, content]
+            .iter()
+            .flat_map(|part| part.bytes().chain([0]))
+            .fold(0xcbf29ce484222325u64, |hash, byte| {
+                (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+            });
+
+        self.directory.join(format!("{:016x}.json", hash))
+    }
+
+    // The whole key is compared, so a hash collision is a cache miss instead of wrong output.
+    // Entries contain the output and the number of tabs groups in it.
+    pub fn get(&self, path: &str, content: &str) -> Option<(String, usize)> {
+        let entry: Entry =
+            serde_json::from_slice(&fs::read(self.entry_path(path, content)).ok()?).ok()?;
+
+        (entry.key == self.key && entry.path == path && entry.content == content)
+            .then_some((entry.output, entry.groups))
+    }
+
+    pub fn set(&self, path: &str, content: &str, output: &str, groups: usize) {
+        let entry = Entry {
+            key: self.key.clone(),
+            path: path.to_string(),
+            content: content.to_string(),
+            output: output.to_string(),
+            groups,
+        };
+
+        if let Err(error) = serde_json::to_vec(&entry)
+            .map_err(anyhow::Error::from)
+            .and_then(|data| Ok(fs::write(self.entry_path(path, content), data)?))
+        {
+            warn!("Failed to write tabs cache for `{}`: {}", path, error);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{env, process};
+
+    use test_log::test;
+
+    use super::*;
+
+    #[test]
+    fn test_cache() -> Result<()> {
+        let directory = env::temp_dir().join(format!("mdbook-tabs-cache-test-{}", process::id()));
+
+        let config = PreprocessorConfig::default();
+        let cache = Cache::new(directory.clone(), &config)?;
+
+        assert_eq!(None, cache.get("chapter.md", "Content."));
+
+        cache.set("chapter.md", "Content.", "Output.", 1);
+        assert_eq!(
+            Some(("Output.".to_string(), 1)),
+            cache.get("chapter.md", "Content.")
+        );
+        assert_eq!(None, cache.get("chapter.md", "Other content."));
+        assert_eq!(None, cache.get("other.md", "Content."));
+
+        let other_config = PreprocessorConfig {
+            sync: true,
+            ..Default::default()
+        };
+        let other_cache = Cache::new(directory.clone(), &other_config)?;
+        assert_eq!(None, other_cache.get("chapter.md", "Content."));
+
+        let other_renderer = PreprocessorConfig {
+            renderer: "markdown".to_string(),
+            ..Default::default()
+        };
+        let other_cache = Cache::new(directory.clone(), &other_renderer)?;
+        assert_eq!(None, other_cache.get("chapter.md", "Content."));
+
+        fs::remove_dir_all(directory)?;
+
+        Ok(())
+    }
+}
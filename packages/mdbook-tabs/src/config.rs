@@ -1,27 +1,398 @@
-use anyhow::Result;
+use std::{ops::Range, path::PathBuf};
+
+use anyhow::{bail, Context, Result};
+use log::warn;
+use mdbook::{preprocess::PreprocessorContext, Config};
+use mdbook_plugin_utils::markdown::{parse_marker, BlockLimits, MarkerAttributes};
+use pulldown_cmark::HeadingLevel;
 use serde::{Deserialize, Serialize};
 
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Responsive {
+    Accordion,
+}
+
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Syntax {
+    #[default]
+    Marker,
+    Colon,
+}
+
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Delimiter {
+    #[default]
+    Marker,
+    Heading,
+    Bold,
+}
+
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RenderMode {
+    #[default]
+    Markdown,
+    Html,
+}
+
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Orientation {
+    #[default]
+    Horizontal,
+    Vertical,
+}
+
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum IdStrategy {
+    #[default]
+    Index,
+    ChapterHash,
+}
+
+const WRAPPER_TAGS: [&str; 5] = ["div", "section", "article", "aside", "figure"];
+
 #[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(default)]
+pub struct PreprocessorConfig {
+    pub keyword: String,
+    pub syntax: Syntax,
+    pub remember: bool,
+    pub sync: bool,
+    pub update_hash: bool,
+    pub class_prefix: String,
+    pub inline_assets: bool,
+    pub heading_level: Option<usize>,
+    pub collapse_single: bool,
+    pub responsive: Option<Responsive>,
+    pub breakpoint: usize,
+    pub max_blocks: Option<usize>,
+    pub max_block_size: Option<usize>,
+    pub pretty: bool,
+    pub empty_placeholder: Option<String>,
+    pub debug_comments: bool,
+    pub lazy: bool,
+    pub id_strategy: IdStrategy,
+    pub cache: bool,
+    pub disabled_chapters: Vec<String>,
+    pub trim_trailing_lines: bool,
+    pub end_markers: Option<Vec<String>>,
+    pub active_class: String,
+    pub hidden_class: String,
+    pub no_js_fallback: bool,
+    pub renderers: Vec<String>,
+    pub orientation: Orientation,
+    pub static_select: Option<String>,
+    pub max_visible_tabs: Option<usize>,
+    pub delimiter: Delimiter,
+    pub badge: bool,
+    pub wrapper_tag: String,
+    pub wrapper_classes: Vec<String>,
+    pub render_mode: RenderMode,
+
+    #[serde(skip)]
+    pub renderer: String,
+
+    // Directory of the book source, data files of tabs are read relative to it.
+    #[serde(skip)]
+    pub src_dir: Option<PathBuf>,
+}
+
+impl Default for PreprocessorConfig {
+    fn default() -> Self {
+        Self {
+            keyword: "tabs".to_string(),
+            syntax: Syntax::Marker,
+            remember: false,
+            sync: false,
+            update_hash: false,
+            class_prefix: "mdbook-".to_string(),
+            inline_assets: false,
+            heading_level: None,
+            collapse_single: false,
+            responsive: None,
+            breakpoint: 600,
+            max_blocks: None,
+            max_block_size: None,
+            pretty: false,
+            empty_placeholder: None,
+            debug_comments: false,
+            lazy: false,
+            id_strategy: IdStrategy::Index,
+            cache: false,
+            disabled_chapters: vec![],
+            trim_trailing_lines: true,
+            end_markers: None,
+            active_class: "active".to_string(),
+            hidden_class: "hidden".to_string(),
+            no_js_fallback: false,
+            renderers: vec!["html".to_string()],
+            orientation: Orientation::Horizontal,
+            static_select: None,
+            max_visible_tabs: None,
+            delimiter: Delimiter::Marker,
+            badge: false,
+            wrapper_tag: "div".to_string(),
+            wrapper_classes: vec![],
+            render_mode: RenderMode::Markdown,
+            renderer: "html".to_string(),
+            src_dir: None,
+        }
+    }
+}
+
+impl PreprocessorConfig {
+    pub fn from_context(ctx: &PreprocessorContext) -> Result<Self> {
+        let mut config = Self::from_config(&ctx.config)?;
+        config.renderer.clone_from(&ctx.renderer);
+        config.src_dir = Some(ctx.root.join(&ctx.config.book.src));
+
+        Ok(config)
+    }
+
+    pub fn from_config(book_config: &Config) -> Result<Self> {
+        let mut config: Self = book_config
+            .get_deserialized_opt("preprocessor.tabs")?
+            .unwrap_or_default();
+
+        config.validate()?;
+
+        Ok(config)
+    }
+
+    pub fn validate(&mut self) -> Result<()> {
+        if self.keyword.contains(char::is_whitespace) {
+            warn!(
+                "Tabs keyword `{}` contains whitespace, which will be ignored.",
+                self.keyword
+            );
+            self.keyword.retain(|c| !c.is_whitespace());
+        }
+
+        if self.keyword.is_empty()
+            || !self
+                .keyword
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+        {
+            bail!(
+                "Tabs keyword `{}` is invalid, it should only contain ASCII letters, digits, `-` and `_`.",
+                self.keyword
+            );
+        }
+
+        for end_marker in self.end_markers.iter().flatten() {
+            let name = parse_marker(end_marker)
+                .filter(|(name, attributes)| !name.is_empty() && attributes.is_empty());
+            if name.is_none() {
+                bail!(
+                    "Tabs end marker `{}` is invalid, it should be a marker without attributes, e.g. `{{{{/{}}}}}`.",
+                    end_marker,
+                    self.keyword
+                );
+            }
+        }
+
+        if let Some(heading_level) = self.heading_level {
+            if HeadingLevel::try_from(heading_level).is_err() {
+                warn!(
+                    "Tabs heading level `{}` is invalid, it should be between 1 and 6. Using tab markers instead.",
+                    heading_level
+                );
+                self.heading_level = None;
+            }
+        }
+
+        match (self.delimiter, self.heading_level) {
+            (Delimiter::Heading, None) => {
+                warn!("Tabs delimiter `heading` requires the `heading_level` option. Using tab markers instead.");
+                self.delimiter = Delimiter::Marker;
+            }
+            (Delimiter::Bold, Some(_)) => {
+                warn!("Tabs option `heading_level` is ignored with the `bold` delimiter.");
+                self.heading_level = None;
+            }
+            _ => {}
+        }
+
+        if !self
+            .class_prefix
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+        {
+            bail!(
+                "Tabs class prefix `{}` is invalid, it should only contain ASCII letters, digits, `-` and `_`.",
+                self.class_prefix
+            );
+        }
+
+        if self.max_visible_tabs == Some(0) {
+            warn!("Tabs option `max_visible_tabs` should be at least 1, showing all tabs.");
+            self.max_visible_tabs = None;
+        }
+
+        if self.lazy && self.no_js_fallback {
+            warn!("Tabs option `lazy` is not supported with `no_js_fallback`, rendering all tab contents.");
+            self.lazy = false;
+        }
+
+        // Only tags that start an HTML block in markdown, so the tabs are not wrapped in a paragraph.
+        if !WRAPPER_TAGS.contains(&self.wrapper_tag.as_str()) {
+            bail!(
+                "Tabs wrapper tag `{}` is invalid, it should be one of {}.",
+                self.wrapper_tag,
+                WRAPPER_TAGS
+                    .iter()
+                    .map(|tag| format!("`{}`", tag))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            );
+        }
+
+        let wrapper_classes = self
+            .wrapper_classes
+            .iter()
+            .map(|class| ("wrapper class", class, true));
+        for (option, class, required) in [
+            ("active class", &self.active_class, true),
+            ("hidden class", &self.hidden_class, false),
+        ]
+        .into_iter()
+        .chain(wrapper_classes)
+        {
+            if (required && class.is_empty())
+                || !class
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+            {
+                bail!(
+                    "Tabs {} `{}` is invalid, it should only contain ASCII letters, digits, `-` and `_`.",
+                    option,
+                    class
+                );
+            }
+        }
+
+        Ok(())
+    }
+
+    // Names of the markers that close tabs, `{{#endtabs}}` and `{{/tabs}}` by default.
+    pub fn end_marker_names(&self) -> Vec<String> {
+        match &self.end_markers {
+            Some(end_markers) => end_markers
+                .iter()
+                .filter_map(|end_marker| parse_marker(end_marker))
+                .map(|(name, _)| name.to_string())
+                .collect(),
+            None => vec![format!("end{}", self.keyword), format!("/{}", self.keyword)],
+        }
+    }
+
+    // Tabs are rendered as HTML for `html` and the configured renderers, e.g. forks of the HTML
+    // renderer. Other renderers get a section for each tab.
+    pub fn is_html_renderer(&self) -> bool {
+        self.renderer == "html" || self.renderers.contains(&self.renderer)
+    }
+
+    pub fn heading_level(&self) -> Option<HeadingLevel> {
+        self.heading_level
+            .and_then(|heading_level| HeadingLevel::try_from(heading_level).ok())
+    }
+
+    pub fn block_limits(&self) -> BlockLimits {
+        BlockLimits {
+            max_blocks: self.max_blocks,
+            max_block_size: self.max_block_size,
+        }
+    }
+
+    pub fn class(&self, name: &str) -> String {
+        format!("{}{}", self.class_prefix, name)
+    }
+}
+
+#[derive(Clone, Debug, Default)]
 pub struct TabsConfig {
     pub global: Option<String>,
+    pub default: Option<String>,
+    pub order: Option<String>,
+    pub title: Option<String>,
+    pub from: Option<String>,
 
-    #[serde(skip)]
     pub tabs: Vec<(TabConfig, String)>,
+    pub content_span: Range<usize>,
+    pub preamble_span: Range<usize>,
+    pub closed: bool,
 }
 
 impl TabsConfig {
-    pub fn parse(content: &str) -> Result<Self, serde_keyvalue::ParseError> {
-        serde_keyvalue::from_key_values(content)
+    pub fn parse(content: &str) -> Result<Self> {
+        let attributes = MarkerAttributes::from_attributes("tabs", content)?;
+
+        Ok(TabsConfig {
+            global: string_attribute(&attributes, "global")?,
+            default: string_attribute(&attributes, "default")?,
+            order: string_attribute(&attributes, "order")?,
+            title: string_attribute(&attributes, "title")?,
+            from: string_attribute(&attributes, "from")?,
+            ..Default::default()
+        })
     }
 }
 
-#[derive(Clone, Debug, Deserialize, Serialize)]
+#[derive(Clone, Debug, Default)]
 pub struct TabConfig {
     pub name: String,
+    pub icon: Option<String>,
+    pub raw: bool,
+
+    pub content_span: Range<usize>,
+    pub closed: bool,
 }
 
 impl TabConfig {
-    pub fn parse(content: &str) -> Result<Self, serde_keyvalue::ParseError> {
-        serde_keyvalue::from_key_values(content)
+    pub fn parse(content: &str) -> Result<Self> {
+        let attributes = MarkerAttributes::from_attributes("tab", content)?;
+
+        let Some(name) = string_attribute(&attributes, "name")? else {
+            bail!("Tab `{}` does not have a `name` attribute.", content);
+        };
+
+        Ok(TabConfig {
+            name,
+            icon: string_attribute(&attributes, "icon")?,
+            raw: flag_attribute(&attributes, "raw")?,
+            ..Default::default()
+        })
+    }
+}
+
+// Unknown attributes are ignored, like unknown options in `book.toml`.
+fn string_attribute(attributes: &MarkerAttributes, key: &str) -> Result<Option<String>> {
+    match attributes.attributes.get(key) {
+        Some(None) => bail!(
+            "Attribute `{}` of `{}` requires a value.",
+            key,
+            attributes.name
+        ),
+        value => Ok(value.cloned().flatten()),
+    }
+}
+
+// Flags without a value are enabled, e.g. `raw`, like `raw=true`.
+fn flag_attribute(attributes: &MarkerAttributes, key: &str) -> Result<bool> {
+    match attributes.attributes.get(key) {
+        None => Ok(false),
+        Some(None) => Ok(true),
+        Some(Some(value)) => value.parse().with_context(|| {
+            format!(
+                "Attribute `{}` of `{}` should be `true` or `false`.",
+                key, attributes.name
+            )
+        }),
     }
 }
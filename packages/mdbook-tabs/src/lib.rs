@@ -1,6 +1,17 @@
+mod builder;
+mod cache;
 mod config;
 mod parser;
 mod preprocessor;
+mod report;
+mod stats;
 mod tabs;
+mod theme;
 
+pub use builder::TabsPreprocessorBuilder;
+pub use config::{
+    Delimiter, IdStrategy, Orientation, PreprocessorConfig, RenderMode, Responsive, Syntax,
+};
 pub use preprocessor::TabsPreprocessor;
+pub use stats::{ChapterStats, TabsStats};
+pub use theme::{css, js};
@@ -1 +1,14 @@
+use pulldown_cmark::Options;
+
+pub mod data;
 pub mod tabs;
+
+// Same extensions as mdbook, except smart punctuation which would turn the quotes of marker
+// attributes into curly quotes.
+pub fn options() -> Options {
+    Options::ENABLE_TABLES
+        | Options::ENABLE_FOOTNOTES
+        | Options::ENABLE_STRIKETHROUGH
+        | Options::ENABLE_TASKLISTS
+        | Options::ENABLE_HEADING_ATTRIBUTES
+}
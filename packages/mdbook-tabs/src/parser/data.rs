@@ -0,0 +1,111 @@
+use anyhow::{bail, Result};
+
+// Removes matching single or double quotes around a YAML scalar.
+fn unquote(value: &str) -> &str {
+    let value = value.trim();
+
+    for quote in ['"', '\''] {
+        if let Some(inner) = value
+            .strip_prefix(quote)
+            .and_then(|value| value.strip_suffix(quote))
+        {
+            return inner;
+        }
+    }
+
+    value
+}
+
+// Parses the labels and files of a tabs data file, in order. Only a flat YAML mapping of labels to
+// files is supported, e.g. `Linux: linux.md`, with comments and optionally quoted scalars.
+pub fn parse_data(content: &str) -> Result<Vec<(String, String)>> {
+    let mut tabs = vec![];
+
+    for (index, line) in content.lines().enumerate() {
+        let trimmed = line.trim();
+        if trimmed.is_empty() || trimmed.starts_with('#') || trimmed == "---" {
+            continue;
+        }
+
+        // The separator is the first colon followed by whitespace, so labels can contain colons
+        // like `C++: Modules` when they are quoted.
+        let separator = match trimmed.starts_with(['"', '\'']) {
+            true => trimmed[1..]
+                .find(&trimmed[..1])
+                .map(|end| end + 2)
+                .filter(|&end| trimmed[end..].starts_with(':')),
+            false => trimmed.find(": "),
+        };
+
+        // Nested mappings and lists are not supported.
+        let mapping = separator
+            .filter(|_| !line.starts_with(char::is_whitespace) && !trimmed.starts_with("- "))
+            .map(|separator| {
+                (
+                    unquote(&trimmed[..separator]),
+                    unquote(
+                        trimmed[separator + 1..]
+                            .split(" #")
+                            .next()
+                            .unwrap_or_default(),
+                    ),
+                )
+            })
+            .filter(|(label, file)| !label.is_empty() && !file.is_empty());
+
+        let Some((label, file)) = mapping else {
+            bail!(
+                "Invalid line {} `{}`, expected a `label: file` mapping.",
+                index + 1,
+                trimmed
+            );
+        };
+
+        tabs.push((label.to_string(), file.to_string()));
+    }
+
+    Ok(tabs)
+}
+
+#[cfg(test)]
+mod test {
+    use test_log::test;
+
+    use super::*;
+
+    #[test]
+    fn test_parse_data() -> Result<()> {
+        let content = "\
+        ---\n\
+        # Install instructions for each platform.\n\
+        Linux: linux.md\n\
+        \"macOS\": 'macos.md' # Also Homebrew.\n\
+        \n\
+        'C++: Modules': cpp/modules.md\n";
+
+        assert_eq!(
+            vec![
+                ("Linux".to_string(), "linux.md".to_string()),
+                ("macOS".to_string(), "macos.md".to_string()),
+                ("C++: Modules".to_string(), "cpp/modules.md".to_string()),
+            ],
+            parse_data(content)?
+        );
+        assert!(parse_data("")?.is_empty());
+
+        for content in [
+            "Linux",
+            "Linux:",
+            "- Linux: linux.md",
+            "Platforms:\n  Linux: linux.md",
+        ] {
+            assert!(
+                parse_data(content).is_err(),
+                "{:?} should be invalid",
+                content
+            );
+        }
+
+        Ok(())
+    }
+}
@@ -1,98 +1,948 @@
 use std::ops::Range;
 
 use anyhow::{bail, Result};
-use log::debug;
-use mdbook::book::Chapter;
-use mdbook_plugin_utils::markdown::parse_blocks;
-use pulldown_cmark::Event;
+use log::trace;
+use mdbook_plugin_utils::markdown::{parse_marker, Block, BlockParseError, BlocksIter};
+use pulldown_cmark::{Event, HeadingLevel, Parser, Tag, TagEnd};
 
-use crate::config::{TabConfig, TabsConfig};
+use crate::{
+    config::{Delimiter, PreprocessorConfig, Syntax, TabConfig, TabsConfig},
+    parser::options,
+};
 
-fn is_tabs_start(event: &Event) -> bool {
+// Markers directly following raw HTML are part of the HTML block, so HTML lines are matched too.
+// Inline code is a separate event, so markers shown as code (e.g. `` `{{#endtabs }}` ``) never match.
+fn marker_text<'e>(event: &'e Event) -> Option<&'e str> {
     match event {
-        Event::Text(text) => {
-            (text.to_string() == "{{#tabs}}" || text.starts_with("{{#tabs"))
-                && !text.contains("{{#endtabs")
+        Event::Text(text) => Some(text),
+        Event::Html(html) => Some(html.trim_end()),
+        _ => None,
+    }
+}
+
+// Blocks without inner content, e.g. unclosed blocks, are empty at the end of their start marker.
+fn inner_span(block: &Block) -> Range<usize> {
+    let start_end = block.start_event().1.end;
+
+    block.inner_span.clone().unwrap_or(start_end..start_end)
+}
+
+fn marker_attributes<'e>(event: &'e Event) -> Option<&'e str> {
+    marker_text(event)
+        .and_then(parse_marker)
+        .map(|(_, attributes)| attributes)
+}
+
+fn is_marker(event: &Event, name: &str) -> bool {
+    marker_text(event)
+        .and_then(parse_marker)
+        .is_some_and(|(marker_name, _)| marker_name == name)
+}
+
+// Marker names of the configuration, built once for each chapter and passed to the parsers.
+pub struct Markers {
+    start: String,
+    ends: Vec<String>,
+    names: Vec<String>,
+}
+
+impl Markers {
+    pub fn new(config: &PreprocessorConfig) -> Self {
+        let start = config.keyword.clone();
+        let ends = config.end_marker_names();
+
+        let mut names = vec![start.clone(), "tab".to_string(), "endtab".to_string()];
+        names.extend(ends.iter().cloned());
+
+        Markers { start, ends, names }
+    }
+
+    fn is_tabs_start(&self, event: &Event) -> bool {
+        is_marker(event, &self.start)
+    }
+
+    fn is_tabs_end(&self, event: &Event) -> bool {
+        self.ends.iter().any(|end| is_marker(event, end))
+    }
+}
+
+fn is_tab_start(event: &Event) -> bool {
+    is_marker(event, "tab")
+}
+
+fn is_tab_end(event: &Event) -> bool {
+    is_marker(event, "endtab")
+}
+
+fn is_escaped(content: &str, offset: usize) -> bool {
+    content[..offset]
+        .bytes()
+        .rev()
+        .take_while(|byte| *byte == b'\\')
+        .count()
+        % 2
+        == 1
+}
+
+// Escaped markers like `\{{#tabs }}` are text without the backslash, so the backslash is added
+// back to the text to not match them as markers.
+fn events(content: &str) -> impl Iterator<Item = (Event<'_>, Range<usize>)> {
+    Parser::new_ext(content, options())
+        .into_offset_iter()
+        .map(|(event, span)| match event {
+            Event::Text(text) if is_escaped(content, span.start) => {
+                (Event::Text(format!("\\{}", text).into()), span)
+            }
+            event => (event, span),
+        })
+}
+
+// Checks if text starting with `{{` starts with one of the markers.
+fn starts_with_marker(text: &str, markers: &Markers) -> bool {
+    text.find("}}")
+        .and_then(|end| parse_marker(&text[..end + 2]))
+        .is_some_and(|(name, _)| markers.names.iter().any(|marker| marker == name))
+}
+
+// Removes the backslash of escaped markers, which are not matched as markers.
+pub fn unescape_markers(content: &str, markers: &Markers) -> String {
+    if !content.contains("\\{{") {
+        return content.to_string();
+    }
+
+    let mut unescaped = String::with_capacity(content.len());
+    let mut offset = 0;
+    let mut code_block = false;
+
+    for (event, span) in events(content) {
+        match event {
+            Event::Start(Tag::CodeBlock(_)) => code_block = true,
+            Event::End(TagEnd::CodeBlock) => code_block = false,
+            Event::Text(text)
+                if !code_block
+                    && text.strip_prefix('\\').is_some_and(|text| {
+                        text.starts_with("{{") && starts_with_marker(text, markers)
+                    })
+                    && is_escaped(content, span.start) =>
+            {
+                unescaped.push_str(&content[offset..span.start - 1]);
+                offset = span.start;
+            }
+            _ => {}
         }
-        _ => false,
     }
+
+    unescaped.push_str(&content[offset..]);
+    unescaped
 }
 
-fn is_tabs_end(event: &Event) -> bool {
-    match event {
-        Event::Text(text) => {
-            (text.to_string() == "{{#endtabs}}" || text.starts_with("{{#endtabs "))
-                && !text.contains("{{#tabs ")
+// Markers are only matched as the whole text of a line, so this finds markers within other text.
+pub fn inline_marker(content: &str, markers: &Markers) -> Option<usize> {
+    let mut code_block = false;
+
+    events(content).find_map(|(event, span)| match event {
+        Event::Start(Tag::CodeBlock(_)) => {
+            code_block = true;
+            None
+        }
+        Event::End(TagEnd::CodeBlock) => {
+            code_block = false;
+            None
         }
-        _ => false,
+        Event::Text(text) if !code_block => {
+            if parse_marker(&text).is_some() {
+                return None;
+            }
+
+            // Escaped markers are at the start of the text, after the added backslash.
+            text.match_indices("{{")
+                .find(|(index, _)| {
+                    !text[..*index].ends_with('\\') && starts_with_marker(&text[*index..], markers)
+                })
+                .map(|(index, _)| span.start + index)
+        }
+        _ => None,
+    })
+}
+
+fn colon_marker<'e>(event: &'e Event) -> Option<&'e str> {
+    marker_text(event).and_then(|text| text.strip_prefix(":::"))
+}
+
+fn is_colon_start(event: &Event) -> bool {
+    colon_marker(event).is_some_and(|rest| rest.starts_with(|c: char| c.is_alphanumeric()))
+}
+
+fn is_colon_end(event: &Event) -> bool {
+    colon_marker(event) == Some("")
+}
+
+fn colon_attributes<'t>(text: &'t str, name: &str) -> Option<&'t str> {
+    let rest = text.strip_prefix(":::")?.strip_prefix(name)?;
+
+    match rest.is_empty() || rest.starts_with(char::is_whitespace) {
+        true => Some(rest.trim()),
+        false => None,
     }
 }
 
-fn is_tab_start(event: &Event) -> bool {
-    match event {
-        Event::Text(text) => text.to_string() == "{{#tab}}" || text.starts_with("{{#tab"),
-        _ => false,
+fn parse_heading_tabs<IsStartFn, IsEndFn>(
+    content: &str,
+    level: HeadingLevel,
+    is_start: IsStartFn,
+    is_end: IsEndFn,
+) -> (usize, Vec<(TabConfig, String)>)
+where
+    IsStartFn: Fn(&Event) -> bool,
+    IsEndFn: Fn(&Event) -> bool,
+{
+    let mut tabs: Vec<(TabConfig, String)> = vec![];
+    let mut preamble_end: Option<usize> = None;
+    let mut depth: usize = 0;
+    let mut heading: Option<(Range<usize>, Option<Range<usize>>)> = None;
+    let mut heading_icon: Option<String> = None;
+    let mut heading_raw = false;
+
+    let mut code_block = false;
+
+    for (event, span) in events(content) {
+        match event {
+            Event::Start(Tag::CodeBlock(_)) => code_block = true,
+            Event::End(TagEnd::CodeBlock) => code_block = false,
+            _ if code_block => {}
+            _ if is_start(&event) => depth += 1,
+            _ if is_end(&event) => depth = depth.saturating_sub(1),
+            _ => {}
+        }
+
+        if depth > 0 {
+            continue;
+        }
+
+        match event {
+            Event::Start(Tag::Heading {
+                level: heading_level,
+                attrs,
+                ..
+            }) if heading_level == level => {
+                if let Some((tab, _)) = tabs.last_mut() {
+                    tab.content_span.end = span.start;
+                }
+                preamble_end.get_or_insert(span.start);
+
+                heading_icon = attrs
+                    .iter()
+                    .find(|(key, _)| key.as_ref() == "icon")
+                    .and_then(|(_, value)| value.as_ref())
+                    .map(|value| value.trim_matches('"').to_string());
+                heading_raw = attrs.iter().any(|(key, _)| key.as_ref() == "raw");
+
+                heading = Some((span, None));
+            }
+            Event::End(TagEnd::Heading(heading_level)) if heading_level == level => {
+                if let Some((heading_span, name_span)) = heading.take() {
+                    let name = name_span.map_or("", |name_span| &content[name_span]);
+
+                    tabs.push((
+                        TabConfig {
+                            name: name.trim().to_string(),
+                            icon: heading_icon.take(),
+                            raw: heading_raw,
+                            content_span: heading_span.end..content.len(),
+                            closed: true,
+                        },
+                        String::new(),
+                    ));
+                }
+            }
+            _ => {
+                if let Some((_, name_span)) = &mut heading {
+                    *name_span = Some(match name_span {
+                        Some(name_span) => name_span.start..span.end,
+                        None => span,
+                    });
+                }
+            }
+        }
+    }
+
+    for (tab, tab_content) in &mut tabs {
+        *tab_content = content[tab.content_span.clone()].to_string();
     }
+
+    (preamble_end.unwrap_or(content.len()), tabs)
 }
 
-fn is_tab_end(event: &Event) -> bool {
-    match event {
-        Event::Text(text) => text.to_string() == "{{#endtab}}" || text.starts_with("{{#endtab "),
-        _ => false,
+// Paragraphs with only bold text, e.g. `**Linux**`, return the span of the paragraph and of the
+// bold text.
+fn bold_paragraph(events: &[(Event, Range<usize>)]) -> Option<(Range<usize>, Range<usize>)> {
+    let [(Event::Start(Tag::Paragraph), span), (Event::Start(Tag::Strong), _), rest @ ..] = events
+    else {
+        return None;
+    };
+
+    let end = rest
+        .iter()
+        .position(|(event, _)| matches!(event, Event::End(TagEnd::Strong)))?;
+    if end == 0 || !matches!(rest.get(end + 1), Some((Event::End(TagEnd::Paragraph), _))) {
+        return None;
+    }
+
+    Some((span.clone(), rest[0].1.start..rest[end - 1].1.end))
+}
+
+// Bold paragraphs separate tabs like headings, but do not add the tab names to the outline of the
+// page. Bold paragraphs in lists and block quotes are part of the tab content.
+fn parse_bold_tabs<IsStartFn, IsEndFn>(
+    content: &str,
+    is_start: IsStartFn,
+    is_end: IsEndFn,
+) -> (usize, Vec<(TabConfig, String)>)
+where
+    IsStartFn: Fn(&Event) -> bool,
+    IsEndFn: Fn(&Event) -> bool,
+{
+    let mut tabs: Vec<(TabConfig, String)> = vec![];
+    let mut preamble_end: Option<usize> = None;
+    let mut depth: usize = 0;
+    let mut containers: usize = 0;
+
+    let mut code_block = false;
+
+    let events = events(content).collect::<Vec<_>>();
+    for (index, (event, _)) in events.iter().enumerate() {
+        match event {
+            Event::Start(Tag::CodeBlock(_)) => code_block = true,
+            Event::End(TagEnd::CodeBlock) => code_block = false,
+            _ if code_block => {}
+            _ if is_start(event) => depth += 1,
+            _ if is_end(event) => depth = depth.saturating_sub(1),
+            Event::Start(Tag::BlockQuote(_) | Tag::Item | Tag::FootnoteDefinition(_)) => {
+                containers += 1
+            }
+            Event::End(TagEnd::BlockQuote | TagEnd::Item | TagEnd::FootnoteDefinition) => {
+                containers = containers.saturating_sub(1)
+            }
+            _ => {}
+        }
+
+        if depth > 0 || containers > 0 {
+            continue;
+        }
+
+        let Some((span, name_span)) = bold_paragraph(&events[index..]) else {
+            continue;
+        };
+
+        if let Some((tab, _)) = tabs.last_mut() {
+            tab.content_span.end = span.start;
+        }
+        preamble_end.get_or_insert(span.start);
+
+        tabs.push((
+            TabConfig {
+                name: content[name_span].trim().to_string(),
+                content_span: span.end..content.len(),
+                closed: true,
+                ..Default::default()
+            },
+            String::new(),
+        ));
+    }
+
+    for (tab, tab_content) in &mut tabs {
+        *tab_content = content[tab.content_span.clone()].to_string();
     }
+
+    (preamble_end.unwrap_or(content.len()), tabs)
+}
+
+// Tabs separated by headings or bold paragraphs instead of tab markers.
+fn delimited_tabs<IsStartFn, IsEndFn>(
+    content: &str,
+    config: &PreprocessorConfig,
+    is_start: IsStartFn,
+    is_end: IsEndFn,
+) -> Option<(usize, Vec<(TabConfig, String)>)>
+where
+    IsStartFn: Fn(&Event) -> bool,
+    IsEndFn: Fn(&Event) -> bool,
+{
+    match (config.delimiter, config.heading_level()) {
+        (Delimiter::Bold, _) => Some(parse_bold_tabs(content, is_start, is_end)),
+        (_, Some(level)) => Some(parse_heading_tabs(content, level, is_start, is_end)),
+        _ => None,
+    }
+}
+
+fn parse_blocks<'a, IsStartFn, IsEndFn>(
+    content: &'a str,
+    is_start: IsStartFn,
+    is_end: IsEndFn,
+    config: &PreprocessorConfig,
+) -> Result<Vec<Block<'a>>, BlockParseError>
+where
+    IsStartFn: Fn(&Event) -> bool,
+    IsEndFn: Fn(&Event) -> bool,
+{
+    BlocksIter::new(events(content), is_start, is_end, usize::MAX)
+        .with_limits(config.block_limits())
+        .collect()
 }
 
-pub fn parse_tabs(chapter: &Chapter) -> Result<Vec<(Range<usize>, TabsConfig)>> {
+fn offset_tabs(tabs: Vec<(TabConfig, String)>, offset: usize) -> Vec<(TabConfig, String)> {
+    tabs.into_iter()
+        .map(|(mut tab, tab_content)| {
+            tab.content_span = (offset + tab.content_span.start)..(offset + tab.content_span.end);
+            (tab, tab_content)
+        })
+        .collect()
+}
+
+fn colon_tabs_blocks<'b, 'a>(
+    blocks: &'b [Block<'a>],
+    config: &PreprocessorConfig,
+    tabs_blocks: &mut Vec<(&'b Block<'a>, TabsConfig)>,
+) -> Result<()> {
+    for block in blocks {
+        match marker_text(&block.start_event().0)
+            .and_then(|text| colon_attributes(text, &config.keyword))
+        {
+            Some(attributes) => tabs_blocks.push((block, TabsConfig::parse(attributes)?)),
+            None => colon_tabs_blocks(&block.children, config, tabs_blocks)?,
+        }
+    }
+
+    Ok(())
+}
+
+fn parse_colon_tabs(
+    content: &str,
+    config: &PreprocessorConfig,
+) -> Result<Vec<(Range<usize>, TabsConfig)>> {
+    let mut configs: Vec<(Range<usize>, TabsConfig)> = vec![];
+
+    let blocks = parse_blocks(content, is_colon_start, is_colon_end, config)?;
+    trace!("{:?}", blocks);
+
+    let mut tabs_blocks = vec![];
+    colon_tabs_blocks(&blocks, config, &mut tabs_blocks)?;
+
+    for (block, mut tabs) in tabs_blocks {
+        let block_span = inner_span(block);
+        tabs.content_span = block_span.clone();
+        tabs.closed = block.closed;
+
+        if let Some((preamble_end, delimited)) = delimited_tabs(
+            block.content(content)?,
+            config,
+            is_colon_start,
+            is_colon_end,
+        ) {
+            tabs.preamble_span = block_span.start..block_span.start + preamble_end;
+            tabs.tabs = offset_tabs(delimited, block_span.start);
+        } else {
+            tabs.preamble_span = block_span;
+
+            for child in &block.children {
+                let Some(attributes) = marker_text(&child.start_event().0)
+                    .and_then(|text| colon_attributes(text, "tab"))
+                else {
+                    continue;
+                };
+
+                let mut tab = TabConfig::parse(attributes)?;
+                tab.content_span = inner_span(child);
+                tab.closed = child.closed;
+
+                if tabs.tabs.is_empty() {
+                    tabs.preamble_span.end = child.span.start;
+                }
+
+                tabs.tabs.push((tab, child.content(content)?.to_string()));
+            }
+        }
+
+        configs.push((block.span.clone(), tabs));
+    }
+
+    trace!("{:?}", configs);
+
+    Ok(configs)
+}
+
+pub fn parse_tabs(
+    content: &str,
+    config: &PreprocessorConfig,
+    markers: &Markers,
+) -> Result<Vec<(Range<usize>, TabsConfig)>> {
+    if config.syntax == Syntax::Colon {
+        return parse_colon_tabs(content, config);
+    }
+
     let mut configs: Vec<(Range<usize>, TabsConfig)> = vec![];
 
-    let blocks = parse_blocks(&chapter.content, is_tabs_start, is_tabs_end)?;
-    debug!("{:?}", blocks);
+    let blocks = parse_blocks(
+        content,
+        |event| markers.is_tabs_start(event),
+        |event| markers.is_tabs_end(event),
+        config,
+    )?;
+    trace!("{:?}", blocks);
 
     for block in blocks {
-        let start_text = match &block.events[0].0 {
-            Event::Text(text) => text.to_string(),
-            _ => bail!("First event should be text."),
+        let Some(attributes) = marker_attributes(&block.start_event().0) else {
+            bail!("First event should be a marker.");
         };
 
-        let mut tabs = TabsConfig::parse(
-            start_text
-                .trim_start_matches("{{#tabs")
-                .trim_start()
-                .trim_end_matches("}}")
-                .trim_end(),
-        )?;
+        let mut tabs = TabsConfig::parse(attributes)?;
 
-        let subblocks = parse_blocks(
-            &chapter.content[block.inner_span.clone()],
-            is_tab_start,
-            is_tab_end,
-        )?;
-        debug!("{:?}", subblocks);
+        let block_span = inner_span(&block);
+        tabs.content_span = block_span.clone();
+        tabs.closed = block.closed;
+
+        let inner_content = block.content(content)?;
+
+        if let Some((preamble_end, delimited)) = delimited_tabs(
+            inner_content,
+            config,
+            |event| markers.is_tabs_start(event),
+            |event| markers.is_tabs_end(event),
+        ) {
+            tabs.preamble_span = block_span.start..block_span.start + preamble_end;
+            tabs.tabs = offset_tabs(delimited, block_span.start);
+
+            configs.push((block.span, tabs));
+            continue;
+        }
+
+        let subblocks = parse_blocks(inner_content, is_tab_start, is_tab_end, config)
+            .map_err(|error| error.with_offset(block_span.start))?;
+        trace!("{:?}", subblocks);
+
+        tabs.preamble_span = block_span.start
+            ..block_span.start
+                + subblocks
+                    .first()
+                    .map_or(inner_content.len(), |subblock| subblock.span.start);
 
         for subblock in subblocks {
-            let start_text = match &subblock.events[0].0 {
-                Event::Text(text) => text.to_string(),
-                _ => bail!("First event should be text."),
+            let Some(attributes) = marker_attributes(&subblock.start_event().0) else {
+                bail!("First event should be a marker.");
             };
 
-            tabs.tabs.push((
-                TabConfig::parse(
-                    start_text
-                        .trim_start_matches("{{#tab")
-                        .trim_start()
-                        .trim_end_matches("}}")
-                        .trim_end(),
-                )?,
-                chapter.content[(block.inner_span.start + subblock.inner_span.start)
-                    ..(block.inner_span.start + subblock.inner_span.end)]
-                    .to_string(),
-            ));
+            let mut tab = TabConfig::parse(attributes)?;
+            let subblock_span = inner_span(&subblock);
+            tab.content_span =
+                (block_span.start + subblock_span.start)..(block_span.start + subblock_span.end);
+            tab.closed = subblock.closed;
+
+            tabs.tabs
+                .push((tab, subblock.content(inner_content)?.to_string()));
         }
 
         configs.push((block.span, tabs));
     }
 
-    debug!("{:?}", configs);
+    trace!("{:?}", configs);
 
     Ok(configs)
 }
+
+#[cfg(test)]
+mod test {
+    use test_log::test;
+
+    use super::*;
+
+    #[test]
+    fn test_parse_tabs_heading_level() -> Result<()> {
+        let content = "\
+        {{#tabs }}\n\
+        \n\
+        #### Linux\n\
+        \n\
+        ##### Debian\n\
+        \n\
+        Install with `apt`.\n\
+        \n\
+        #### `macOS` {icon=\"apple\"}\n\
+        \n\
+        Install with `brew`.\n\
+        \n\
+        {{#endtabs }}";
+
+        let config = PreprocessorConfig {
+            heading_level: Some(4),
+            ..Default::default()
+        };
+        let actual = parse_tabs(content, &config, &Markers::new(&config))?;
+
+        assert_eq!(1, actual.len());
+
+        let tabs = &actual[0].1.tabs;
+        assert_eq!(
+            vec!["Linux", "`macOS`"],
+            tabs.iter()
+                .map(|(tab, _)| tab.name.as_str())
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(
+            vec![None, Some("apple")],
+            tabs.iter()
+                .map(|(tab, _)| tab.icon.as_deref())
+                .collect::<Vec<_>>()
+        );
+        assert_eq!("\n##### Debian\n\nInstall with `apt`.\n\n", tabs[0].1);
+        assert_eq!("\nInstall with `brew`.\n\n", tabs[1].1);
+        assert_eq!(tabs[1].1, &content[tabs[1].0.content_span.clone()]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_tabs_bold_delimiter() -> Result<()> {
+        let content = "\
+        {{#tabs }}\n\
+        Shared.\n\
+        \n\
+        **Linux**\n\
+        \n\
+        Install with **apt**.\n\
+        \n\
+        - **Note**\n\
+        \n\
+        \x20 In a list.\n\
+        \n\
+        **`macOS`**\n\
+        \n\
+        ```\n\
+        **Code**\n\
+        ```\n\
+        \n\
+        > **Quote**\n\
+        \n\
+        #### Heading\n\
+        \n\
+        {{#endtabs }}";
+
+        for syntax in [Syntax::Marker, Syntax::Colon] {
+            let content = match syntax {
+                Syntax::Marker => content.to_string(),
+                Syntax::Colon => content
+                    .replace("{{#tabs }}", ":::tabs")
+                    .replace("{{#endtabs }}", ":::"),
+            };
+            let config = PreprocessorConfig {
+                syntax,
+                delimiter: Delimiter::Bold,
+                heading_level: Some(4),
+                ..Default::default()
+            };
+            let actual = parse_tabs(&content, &config, &Markers::new(&config))?;
+
+            assert_eq!(1, actual.len());
+
+            let tabs_config = &actual[0].1;
+            assert_eq!("\nShared.\n\n", &content[tabs_config.preamble_span.clone()]);
+
+            let tabs = &tabs_config.tabs;
+            assert_eq!(
+                vec!["Linux", "`macOS`"],
+                tabs.iter()
+                    .map(|(tab, _)| tab.name.as_str())
+                    .collect::<Vec<_>>()
+            );
+            assert_eq!(
+                "\nInstall with **apt**.\n\n- **Note**\n\n  In a list.\n\n",
+                tabs[0].1
+            );
+            assert_eq!(
+                "\n```\n**Code**\n```\n\n> **Quote**\n\n#### Heading\n\n",
+                tabs[1].1
+            );
+            assert_eq!(tabs[1].1, &content[tabs[1].0.content_span.clone()]);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_tabs_inline_marker() -> Result<()> {
+        let content = "\
+        Use {{#tabs }} to start tabs.\n\
+        \n\
+        {{#tabs }} with text\n\
+        {{#tab name=\"Linux\" }}\n\
+        Linux.\n\
+        {{#endtab }}\n\
+        {{#endtabs }}\n\
+        \n\
+        Inline `{{#tabs }}` code.";
+
+        let config = PreprocessorConfig::default();
+
+        assert!(parse_tabs(content, &config, &Markers::new(&config))?.is_empty());
+        assert_eq!(Some(4), inline_marker(content, &Markers::new(&config)));
+        assert_eq!(
+            Some(0),
+            inline_marker("{{#tabs }} with text", &Markers::new(&config))
+        );
+        assert_eq!(
+            None,
+            inline_marker(
+                "{{#tabs }}\n\nInline `{{#tabs }}` code.",
+                &Markers::new(&config)
+            )
+        );
+        assert_eq!(
+            None,
+            inline_marker(
+                "```markdown\nSome text {{#tabs }}\n```",
+                &Markers::new(&config)
+            )
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_tabs_marker_whitespace() -> Result<()> {
+        let content = "\
+        {{# tabs global=\"os\" }}\n\
+        {{#tab\tname=\"Linux\"}}\n\
+        Linux.\n\
+        {{# endtab }}\n\
+        {{#tab  name=\"macOS\"\t}}\n\
+        macOS.\n\
+        {{#endtab}}\n\
+        {{#endtabs\t}}";
+
+        let config = PreprocessorConfig::default();
+        let tabs = parse_tabs(content, &config, &Markers::new(&config))?;
+        assert_eq!(1, tabs.len());
+
+        let (span, tabs) = &tabs[0];
+        assert_eq!(0..content.len(), *span);
+        assert_eq!(Some("os".to_string()), tabs.global);
+        assert!(tabs.closed);
+        assert_eq!(
+            vec![("Linux", "\nLinux.\n"), ("macOS", "\nmacOS.\n")],
+            tabs.tabs
+                .iter()
+                .map(|(tab, tab_content)| (tab.name.as_str(), tab_content.as_str()))
+                .collect::<Vec<_>>()
+        );
+
+        assert_eq!(
+            Some(4),
+            inline_marker("Use {{# tabs }} to start tabs.", &Markers::new(&config))
+        );
+        assert_eq!(
+            None,
+            inline_marker(
+                "Use {{# tabset }} or {{#tabs-disable}}.",
+                &Markers::new(&config)
+            )
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_tabs_marker_attributes() -> Result<()> {
+        let content = "\
+        {{#tabs default=\"Hi\" order=\"Hi, Config\" }}\n\
+        {{#tab name=\"Config\" raw }}\n\
+        Config.\n\
+        {{#endtab }}\n\
+        {{#tab name=\"Hi\",icon=\"wave\",raw=false }}\n\
+        Hi.\n\
+        {{#endtab }}\n\
+        {{#endtabs }}";
+
+        let config = PreprocessorConfig::default();
+        let tabs = parse_tabs(content, &config, &Markers::new(&config))?;
+
+        let (_, tabs) = &tabs[0];
+        assert_eq!(Some("Hi".to_string()), tabs.default);
+        assert_eq!(Some("Hi, Config".to_string()), tabs.order);
+
+        let (config_tab, _) = &tabs.tabs[0];
+        assert_eq!("Config", config_tab.name);
+        assert!(config_tab.raw);
+
+        let (hi_tab, _) = &tabs.tabs[1];
+        assert_eq!("Hi", hi_tab.name);
+        assert_eq!(Some("wave".to_string()), hi_tab.icon);
+        assert!(!hi_tab.raw);
+
+        // Markdown escapes split the text of markers, but attributes can contain escaped quotes, e.g.
+        // in markers after HTML.
+        let tab = TabConfig::parse("name=\"Say \\\"hi\\\"\" raw=true")?;
+        assert_eq!("Say \"hi\"", tab.name);
+        assert!(tab.raw);
+
+        for attributes in ["raw", "name=Linux", "name=\"Linux\",raw=yes", "name"] {
+            assert!(
+                TabConfig::parse(attributes).is_err(),
+                "{:?} should be invalid",
+                attributes
+            );
+        }
+        assert!(TabsConfig::parse("global").is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_tabs_end_markers() -> Result<()> {
+        let content = "\
+        {{#tabs }}\n\
+        {{#tab name=\"Linux\" }}\n\
+        Linux.\n\
+        {{#endtab }}\n\
+        {{/tabs}}\n\
+        \n\
+        {{#tabs }}\n\
+        {{#tab name=\"macOS\" }}\n\
+        macOS.\n\
+        {{#endtab }}\n\
+        {{#endtabs }}\n\
+        \n\
+        {{#tabs }}\n\
+        {{#tab name=\"Windows\" }}\n\
+        Windows.\n\
+        {{#endtab }}\n\
+        {{ /tabs }}";
+
+        let config = PreprocessorConfig::default();
+        let tabs = parse_tabs(content, &config, &Markers::new(&config))?;
+
+        assert_eq!(3, tabs.len());
+        assert!(tabs.iter().all(|(_, tabs)| tabs.closed));
+        assert_eq!(
+            vec!["{{/tabs}}", "{{#endtabs }}", "{{ /tabs }}"],
+            tabs.iter()
+                .map(|(span, _)| content[span.clone()].lines().last().unwrap_or_default())
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(None, inline_marker(content, &Markers::new(&config)));
+        assert_eq!(
+            Some(4),
+            inline_marker("End {{/tabs}} here.", &Markers::new(&config))
+        );
+
+        let config = PreprocessorConfig {
+            end_markers: Some(vec!["{{/tabs}}".to_string()]),
+            ..Default::default()
+        };
+        let tabs = parse_tabs(content, &config, &Markers::new(&config))?;
+
+        assert_eq!(2, tabs.len());
+        assert_eq!(0..content.find("\n\n").unwrap(), tabs[0].0);
+        // `{{#endtabs }}` does not close the second tabs, so the third tabs are nested in it.
+        assert!(!tabs[1].1.closed);
+
+        let mut config = PreprocessorConfig {
+            end_markers: Some(vec!["endtabs".to_string()]),
+            ..Default::default()
+        };
+        assert!(config.validate().is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_tabs_table() -> Result<()> {
+        let content = "\
+        {{#tabs }}\n\
+        {{#tab name=\"Linux\" }}\n\
+        \n\
+        | Distribution | Command |\n\
+        | ------------ | ------- |\n\
+        | Debian       | `apt`   |\n\
+        \n\
+        {{#endtab }}\n\
+        {{#tab name=\"macOS\" }}\n\
+        \n\
+        - [x] ~~Install Xcode~~\n\
+        \n\
+        {{#endtab }}\n\
+        {{#endtabs }}";
+
+        let actual = parse_tabs(
+            content,
+            &PreprocessorConfig::default(),
+            &Markers::new(&PreprocessorConfig::default()),
+        )?;
+
+        assert_eq!(1, actual.len());
+
+        let tabs = &actual[0].1.tabs;
+        assert_eq!(2, tabs.len());
+        assert_eq!(
+            "| Distribution | Command |\n| ------------ | ------- |\n| Debian       | `apt`   |\n",
+            tabs[0].1
+        );
+        assert!(Parser::new_ext(&tabs[0].1, options())
+            .any(|event| matches!(event, Event::Start(Tag::Table(_)))));
+        assert_eq!("- [x] ~~Install Xcode~~\n\n", tabs[1].1);
+        assert_eq!(tabs[1].1, &content[tabs[1].0.content_span.clone()]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_tabs_colon() -> Result<()> {
+        let content = "\
+        :::tabs global=\"example\"\n\
+        :::tab name=\"Linux\"\n\
+        :::note\n\
+        Use `sudo`.\n\
+        :::\n\
+        \n\
+        ```md\n\
+        :::\n\
+        ```\n\
+        :::\n\
+        :::tab name=\"macOS\"\n\
+        Use `brew`.\n\
+        :::\n\
+        :::\n\
+        \n\
+        :::note\n\
+        :::tabs\n\
+        :::tab name=\"Windows\"\n\
+        Use `winget`.\n\
+        :::\n\
+        :::\n\
+        :::";
+
+        let config = PreprocessorConfig {
+            syntax: Syntax::Colon,
+            ..Default::default()
+        };
+        let actual = parse_tabs(content, &config, &Markers::new(&config))?;
+
+        assert_eq!(2, actual.len());
+        assert_eq!(Some("example".to_string()), actual[0].1.global);
+
+        let tabs = &actual[0].1.tabs;
+        assert_eq!(
+            vec!["Linux", "macOS"],
+            tabs.iter()
+                .map(|(tab, _)| tab.name.as_str())
+                .collect::<Vec<_>>()
+        );
+        assert_eq!("\n:::note\nUse `sudo`.\n:::\n\n```md\n:::\n```", tabs[0].1);
+        assert_eq!(tabs[0].1, &content[tabs[0].0.content_span.clone()]);
+        assert_eq!("\nUse `brew`.\n", tabs[1].1);
+
+        assert_eq!("Windows", actual[1].1.tabs[0].0.name);
+        assert!(content[actual[1].0.clone()].starts_with(":::tabs\n"));
+        assert!(content[actual[1].0.clone()].ends_with("Use `winget`.\n:::\n:::"));
+
+        Ok(())
+    }
+}
@@ -1,19 +1,201 @@
-use std::str;
+use std::{env, fs, ops::Range, path::Path, str};
 
-use anyhow::Result;
+use anyhow::{bail, Context, Result};
+use log::{debug, trace, warn};
 use mdbook::{
-    book::Book,
-    preprocess::{Preprocessor, PreprocessorContext},
-    BookItem,
+    book::{Book, Chapter},
+    preprocess::{LinkPreprocessor, Preprocessor, PreprocessorContext},
+    BookItem, MDBook,
 };
 
-use crate::{parser::tabs::parse_tabs, tabs::tabs};
+use mdbook_plugin_utils::markdown::{parse_marker, position, BlockParseError};
+use pulldown_cmark::{Event, Parser, Tag, TagEnd};
 
-pub struct TabsPreprocessor;
+use crate::{
+    builder::TabsPreprocessorBuilder,
+    cache::{Cache, CACHE_DIRECTORY},
+    config::{IdStrategy, PreprocessorConfig, TabConfig, TabsConfig},
+    parser::{
+        data::parse_data,
+        options,
+        tabs::{inline_marker, parse_tabs, unescape_markers, Markers},
+    },
+    report::{write_report, TabsReport, REPORT_VARIABLE},
+    stats::TabsStats,
+    tabs::{active_index, order_tabs, raw, sections, tabs},
+    theme::{css, js},
+};
+
+pub struct TabsPreprocessor {
+    config: Option<PreprocessorConfig>,
+}
 
 impl TabsPreprocessor {
     pub fn new() -> Self {
-        Self
+        Self { config: None }
+    }
+
+    pub fn builder() -> TabsPreprocessorBuilder {
+        TabsPreprocessorBuilder::new()
+    }
+
+    pub(crate) fn with_config(config: PreprocessorConfig) -> Self {
+        Self {
+            config: Some(config),
+        }
+    }
+
+    pub fn render_block(&self, content: &str) -> Result<String> {
+        let config = self.config.clone().unwrap_or_default();
+        let mut ctx = ChapterContext::new(&config, "<block>".to_string(), content);
+
+        if parse_tabs(content, &config, &ctx.markers)?.is_empty() {
+            bail!("Content does not contain tabs.");
+        }
+
+        process_content(&mut ctx, content, &Offsets::default())
+    }
+
+    // CSS and JS installed or inlined for the configuration, e.g. to check the effect of options.
+    pub fn generated_css(&self) -> String {
+        css(&self.config.clone().unwrap_or_default())
+    }
+
+    pub fn generated_js(&self) -> String {
+        js(&self.config.clone().unwrap_or_default())
+    }
+
+    pub fn strip_markers(content: &str) -> String {
+        let config = PreprocessorConfig {
+            renderer: "markdown".to_string(),
+            ..Default::default()
+        };
+
+        match process_content(
+            &mut ChapterContext::new(&config, "<content>".to_string(), content),
+            content,
+            &Offsets::default(),
+        ) {
+            Ok(stripped) => stripped,
+            Err(error) => {
+                warn!("Failed to strip tabs markers: {}", error);
+                content.to_string()
+            }
+        }
+    }
+
+    pub fn validate(&self, book: &Book, config: &PreprocessorConfig) -> Vec<String> {
+        let mut problems = vec![];
+
+        for item in book.iter() {
+            if let BookItem::Chapter(chapter) = item {
+                let ctx = ChapterContext::from_chapter(config, chapter);
+                if disabled_content(&ctx).is_none() {
+                    validate_content(&ctx, &chapter.content, 0, &mut problems);
+                }
+            }
+        }
+
+        problems
+    }
+
+    // Validates a loaded book with the configuration of `book.toml`, so tabs read with `from` are
+    // resolved in the source directory of the book like in `mdbook build`. Links are expanded
+    // first, since tabs can be split across included files.
+    pub fn validate_book(&self, book: &MDBook) -> Result<Vec<String>> {
+        let ctx: PreprocessorContext = serde_json::from_value(serde_json::json!({
+            "root": book.root,
+            "config": book.config,
+            "renderer": "html",
+            "mdbook_version": mdbook::MDBOOK_VERSION,
+        }))?;
+        let config = self.context_config(&ctx)?;
+        let expanded = LinkPreprocessor::new().run(&ctx, book.book.clone())?;
+
+        Ok(self.validate(&expanded, &config))
+    }
+
+    // Runs the preprocessor like `Preprocessor::run`, and returns the number of processed tabs.
+    pub fn run_with_stats(
+        &self,
+        ctx: &PreprocessorContext,
+        book: Book,
+    ) -> Result<(Book, TabsStats)> {
+        let report_path = env::var_os(REPORT_VARIABLE).map(|path| ctx.root.join(path));
+
+        self.run_with_report(ctx, book, report_path.as_deref())
+    }
+
+    // Processes a book without a `PreprocessorContext`, e.g. in tests and tools. The builder
+    // configuration or the defaults are used, with the `html` renderer and without the cache.
+    pub fn process_book(&self, book: Book) -> Result<Book> {
+        let config = PreprocessorConfig {
+            renderer: "html".to_string(),
+            ..self.config.clone().unwrap_or_default()
+        };
+        let mut book = book;
+
+        process_items(
+            &mut book.sections,
+            &config,
+            None,
+            &mut vec![],
+            &mut TabsStats::default(),
+        )?;
+
+        Ok(book)
+    }
+
+    fn context_config(&self, ctx: &PreprocessorContext) -> Result<PreprocessorConfig> {
+        match &self.config {
+            Some(config) => Ok(PreprocessorConfig {
+                renderer: ctx.renderer.clone(),
+                src_dir: Some(ctx.root.join(&ctx.config.book.src)),
+                ..config.clone()
+            }),
+            None => PreprocessorConfig::from_context(ctx),
+        }
+    }
+
+    fn run_with_report(
+        &self,
+        ctx: &PreprocessorContext,
+        book: Book,
+        report_path: Option<&Path>,
+    ) -> Result<(Book, TabsStats)> {
+        let config = self.context_config(ctx)?;
+        let mut book = book.clone();
+        debug!("Processing tabs for renderer `{}`.", config.renderer);
+
+        if config.renderer == "html" && !config.inline_assets && !has_additional_css(ctx) {
+            warn!(
+                "The tabs CSS is not included in `output.html.additional-css`. \
+                Run `mdbook-tabs install` and add `theme/tabs.css` and `theme/tabs.js` to the HTML renderer, \
+                or enable `inline_assets`."
+            );
+        }
+
+        // Cached chapters are not processed, so the cache is skipped when a report is written.
+        let cache = match config.cache && report_path.is_none() {
+            true => Some(Cache::new(ctx.root.join(CACHE_DIRECTORY), &config)?),
+            false => None,
+        };
+
+        let mut report = vec![];
+        let mut stats = TabsStats::default();
+        process_items(
+            &mut book.sections,
+            &config,
+            cache.as_ref(),
+            &mut report,
+            &mut stats,
+        )?;
+
+        if let Some(report_path) = report_path {
+            write_report(report_path, &report)?;
+        }
+
+        Ok((book, stats))
     }
 }
 
@@ -28,39 +210,2742 @@ impl Preprocessor for TabsPreprocessor {
         "tabs"
     }
 
-    fn run(&self, _ctx: &PreprocessorContext, book: Book) -> Result<Book> {
-        let mut book = book.clone();
-
-        process_items(&mut book.sections)?;
-
-        Ok(book)
+    fn run(&self, ctx: &PreprocessorContext, book: Book) -> Result<Book> {
+        self.run_with_stats(ctx, book).map(|(book, _)| book)
     }
 
+    // Skipped renderers would get the markers as text, so the preprocessor runs for every renderer.
+    // `run` renders sections instead of tabs for renderers without HTML support, see `renderers`.
     fn supports_renderer(&self, _renderer: &str) -> bool {
         true
     }
 }
 
-fn process_items(items: &mut Vec<BookItem>) -> Result<()> {
+fn has_additional_css(ctx: &PreprocessorContext) -> bool {
+    ctx.config.html_config().is_some_and(|html_config| {
+        html_config
+            .additional_css
+            .iter()
+            .any(|path| path.ends_with("tabs.css"))
+    })
+}
+
+// Assets are inlined in each chapter with tabs, but `print.html` contains all chapters on one page.
+// The script is scoped and only runs once, so its declarations do not conflict and the handlers are
+// not registered for each chapter.
+fn inline_assets(config: &PreprocessorConfig) -> String {
+    format!(
+        "<style>\n{}</style>\n<script>\n{}</script>\n\n",
+        css(config),
+        inline_js(config)
+    )
+}
+
+fn inline_js(config: &PreprocessorConfig) -> String {
+    format!(
+        "(() => {{\nif (window.__mdbookTabs) {{\n    return;\n}}\nwindow.__mdbookTabs = true;\n\n{}}})();\n",
+        js(config)
+    )
+}
+
+struct ChapterContext<'a> {
+    config: &'a PreprocessorConfig,
+    markers: Markers,
+    path: String,
+    source: &'a str,
+    group: usize,
+    tab_depth: usize,
+    report: Vec<TabsReport>,
+    reads_files: bool,
+}
+
+impl<'a> ChapterContext<'a> {
+    fn new(config: &'a PreprocessorConfig, path: String, source: &'a str) -> Self {
+        ChapterContext {
+            config,
+            markers: Markers::new(config),
+            path,
+            source,
+            group: 0,
+            tab_depth: 0,
+            report: vec![],
+            reads_files: false,
+        }
+    }
+
+    fn from_chapter(config: &'a PreprocessorConfig, chapter: &'a Chapter) -> Self {
+        Self::new(
+            config,
+            chapter
+                .source_path
+                .as_ref()
+                .map(|path| path.display().to_string())
+                .unwrap_or(chapter.name.clone()),
+            &chapter.content,
+        )
+    }
+
+    fn group_id(&self) -> String {
+        match self.config.id_strategy {
+            IdStrategy::Index => self.group.to_string(),
+            IdStrategy::ChapterHash => format!("{}-{}", path_hash(&self.path), self.group),
+        }
+    }
+
+    fn location(&self, offset: usize) -> String {
+        let position = position(self.source, offset);
+
+        format!("{}:{}:{}", self.path, position.line, position.column)
+    }
+}
+
+// FNV-1a, which is stable across builds and platforms unlike the standard library hasher.
+fn path_hash(path: &str) -> String {
+    let hash = path
+        .replace('\\', "/")
+        .bytes()
+        .fold(0x811c9dc5u32, |hash, byte| {
+            (hash ^ byte as u32).wrapping_mul(0x01000193)
+        });
+
+    format!("{:08x}", hash)
+}
+
+fn process_items(
+    items: &mut Vec<BookItem>,
+    config: &PreprocessorConfig,
+    cache: Option<&Cache>,
+    report: &mut Vec<TabsReport>,
+    stats: &mut TabsStats,
+) -> Result<()> {
     for section in items {
         if let BookItem::Chapter(chapter) = section {
-            let configs = parse_tabs(chapter)?;
+            let mut ctx = ChapterContext::from_chapter(config, chapter);
+            let path = ctx.path.clone();
 
-            let mut offset: usize = 0;
-
-            for (span, config) in configs {
-                let replacement = tabs(&config);
+            if let Some(content) = disabled_content(&ctx) {
+                debug!("Tabs are disabled in chapter `{}`.", path);
+                stats.push(path, 0);
+                chapter.content = content;
+                process_items(&mut chapter.sub_items, config, cache, report, stats)?;
+                continue;
+            }
 
-                chapter
-                    .content
-                    .replace_range((span.start + offset)..(span.end + offset), &replacement);
+            match cache.and_then(|cache| cache.get(&path, &chapter.content)) {
+                Some((content, groups)) => {
+                    debug!("Using cached tabs of chapter `{}`.", path);
+                    stats.push(path, groups);
+                    chapter.content = content;
+                }
+                None => {
+                    let content = process_chapter(&mut ctx)?;
+                    debug!(
+                        "Processed {} tabs ({} rendered) in chapter `{}`.",
+                        ctx.report.len(),
+                        ctx.group,
+                        path
+                    );
+                    report.append(&mut ctx.report);
 
-                offset += replacement.len() - span.len();
+                    // The output of tabs read from data files depends on more than the content.
+                    if let Some(cache) = cache.filter(|_| !ctx.reads_files) {
+                        cache.set(&path, &chapter.content, &content, ctx.group);
+                    }
+                    stats.push(path, ctx.group);
+                    chapter.content = content;
+                }
             }
 
-            process_items(&mut chapter.sub_items)?;
+            process_items(&mut chapter.sub_items, config, cache, report, stats)?;
         }
     }
 
     Ok(())
 }
+
+// Chapters are disabled by a `{{#tabs-disable}}` line at the start or by `disabled_chapters`.
+// The directive is removed in both cases, the rest of the content is passed through untouched.
+fn disabled_content(ctx: &ChapterContext) -> Option<String> {
+    let directive = format!("{}-disable", ctx.config.keyword);
+
+    let source = ctx.source.trim_start();
+    let (line, rest) = source.split_once('\n').unwrap_or((source, ""));
+    if parse_marker(line) == Some((directive.as_str(), "")) {
+        return Some(rest.to_string());
+    }
+
+    let path = ctx.path.replace('\\', "/");
+    ctx.config
+        .disabled_chapters
+        .iter()
+        .any(|disabled| disabled.replace('\\', "/") == path)
+        .then(|| ctx.source.to_string())
+}
+
+fn process_chapter(ctx: &mut ChapterContext) -> Result<String> {
+    let source = ctx.source;
+    let mut content = process_content(ctx, source, &Offsets::default())?;
+
+    if ctx.group > 0 && ctx.config.inline_assets && ctx.config.is_html_renderer() {
+        content.insert_str(0, &inline_assets(ctx.config));
+    }
+
+    Ok(content)
+}
+
+fn unresolved_link(content: &str) -> Option<usize> {
+    let mut code_block = false;
+
+    Parser::new_ext(content, options())
+        .into_offset_iter()
+        .find_map(|(event, span)| match event {
+            Event::Start(Tag::CodeBlock(_)) => {
+                code_block = true;
+                None
+            }
+            Event::End(TagEnd::CodeBlock) => {
+                code_block = false;
+                None
+            }
+            Event::Text(text) if !code_block => {
+                ["{{#include ", "{{#rustdoc_include ", "{{#playground "]
+                    .iter()
+                    .filter_map(|link| text.find(link))
+                    .min()
+                    .map(|index| span.start + index)
+            }
+            _ => None,
+        })
+}
+
+// Markers between the tabs of a group are not in a gap or a tab, e.g. `{{#endtabs }}{{#tabs }}` on
+// one line, which merges the next group into this one.
+fn marker_between_tabs(
+    content: &str,
+    tabs_config: &TabsConfig,
+    markers: &Markers,
+) -> Option<usize> {
+    let mut offset = tabs_config.preamble_span.end;
+    let mut spans = vec![];
+    for (tab, _) in &tabs_config.tabs {
+        spans.push(offset..tab.content_span.start.max(offset));
+        offset = offset.max(tab.content_span.end);
+    }
+    spans.push(offset..tabs_config.content_span.end.max(offset));
+
+    spans.into_iter().find_map(|span| {
+        inline_marker(&content[span.clone()], markers).map(|index| span.start + index)
+    })
+}
+
+fn push_gap(
+    ctx: &ChapterContext,
+    processed: &mut String,
+    content: &str,
+    offsets: &Offsets,
+    span: Range<usize>,
+) {
+    let gap = &content[span.clone()];
+
+    if let Some(offset) = inline_marker(gap, &ctx.markers) {
+        warn!(
+            "Tabs marker at {} is not on its own line and is ignored.",
+            ctx.location(offsets.get(span.start + offset))
+        );
+    }
+
+    if ctx.tab_depth > 0 {
+        if let Some(offset) = unresolved_link(gap) {
+            warn!(
+                "Tab at {} contains an unresolved `{{{{#include}}}}` or similar link, add `after = [\"links\"]` to the tabs preprocessor configuration.",
+                ctx.location(offsets.get(span.start + offset))
+            );
+        }
+    }
+
+    processed.push_str(&unescape_markers(gap, &ctx.markers));
+}
+
+// Tab names are only unique within tabs, since `sync`, `remember` and `global` match tabs by their
+// set of names and IDs contain the index of the tabs. A later tab with the name of an earlier tab
+// of the same tabs is a duplicate, and gets the index of the tab in its ID.
+fn duplicate_names(tabs_config: &TabsConfig) -> Vec<&str> {
+    tabs_config
+        .tabs
+        .iter()
+        .enumerate()
+        .filter(|(index, (tab, _))| {
+            tabs_config.tabs[..*index]
+                .iter()
+                .any(|(other, _)| other.name == tab.name)
+        })
+        .map(|(_, (tab, _))| tab.name.as_str())
+        .collect()
+}
+
+fn validate_content(ctx: &ChapterContext, content: &str, base: usize, problems: &mut Vec<String>) {
+    let configs = match parse_tabs(content, ctx.config, &ctx.markers) {
+        Ok(configs) => configs,
+        Err(error) => {
+            let offset = error
+                .downcast_ref::<BlockParseError>()
+                .map_or(0, |error| error.span().start);
+            problems.push(format!("{}: {}", ctx.location(base + offset), error));
+            return;
+        }
+    };
+
+    for (span, tabs_config) in configs {
+        let location = format!(
+            "{} ({:?})",
+            ctx.location(base + span.start),
+            (base + span.start)..(base + span.end)
+        );
+
+        if !tabs_config.closed {
+            problems.push(format!("{}: Tabs are not closed.", location));
+            continue;
+        }
+
+        if let Some(from) = &tabs_config.from {
+            if let Err(error) = data_tabs(ctx, from, base + span.start) {
+                problems.push(format!("{}: {:#}", location, error));
+            }
+        } else if tabs_config.tabs.is_empty() {
+            problems.push(format!("{}: Tabs do not contain any tab.", location));
+            validate_content(
+                ctx,
+                &content[tabs_config.content_span.clone()],
+                base + tabs_config.content_span.start,
+                problems,
+            );
+            continue;
+        }
+
+        for (tab, _) in tabs_config.tabs.iter().filter(|(tab, _)| !tab.closed) {
+            problems.push(format!("{}: Tab `{}` is not closed.", location, tab.name));
+        }
+
+        for name in duplicate_names(&tabs_config) {
+            problems.push(format!(
+                "{}: Tabs contain multiple tabs named `{}`.",
+                location, name
+            ));
+        }
+
+        for (tab, tab_content) in tabs_config.tabs.iter().filter(|(tab, _)| !tab.raw) {
+            validate_content(ctx, tab_content, base + tab.content_span.start, problems);
+        }
+    }
+}
+
+// Tabs of a data file, which maps labels to files. Like includes, the data file is relative to the
+// chapter and the files are relative to the data file. Links in the files are not expanded, as
+// mdBook expands them before this preprocessor runs. Without a book, e.g. in `process_book`, the
+// chapter directory is unknown, so data files can not be read.
+fn data_tabs(ctx: &ChapterContext, from: &str, offset: usize) -> Result<Vec<(TabConfig, String)>> {
+    let Some(src_dir) = &ctx.config.src_dir else {
+        bail!(
+            "Tabs at {} read from `{}`, which requires the source directory of the book.",
+            ctx.location(offset),
+            from
+        );
+    };
+
+    let chapter_directory = Path::new(&ctx.path).parent().unwrap_or(Path::new(""));
+    let path = src_dir.join(chapter_directory).join(from);
+
+    let data = fs::read_to_string(&path).with_context(|| {
+        format!(
+            "Failed to read tabs data file `{}` of tabs at {}.",
+            path.display(),
+            ctx.location(offset)
+        )
+    })?;
+    let tabs = parse_data(&data)
+        .with_context(|| format!("Failed to parse tabs data file `{}`.", path.display()))?;
+
+    let directory = path.parent().unwrap_or(Path::new(""));
+    tabs.into_iter()
+        .map(|(name, file)| {
+            let file_path = directory.join(file);
+            let content = fs::read_to_string(&file_path).with_context(|| {
+                format!(
+                    "Failed to read file `{}` of tab `{}` in tabs data file `{}`.",
+                    file_path.display(),
+                    name,
+                    path.display()
+                )
+            })?;
+
+            Ok((
+                TabConfig {
+                    name,
+                    closed: true,
+                    ..Default::default()
+                },
+                content,
+            ))
+        })
+        .collect()
+}
+
+// Offsets in the chapter of the lines of processed content, which is a slice of the chapter or of
+// dedented content. Each line is contiguous in the chapter, so offsets are mapped by their line.
+#[derive(Clone, Debug, Default)]
+struct Offsets(Vec<(usize, usize)>);
+
+impl Offsets {
+    fn get(&self, offset: usize) -> usize {
+        self.0
+            .iter()
+            .rev()
+            .find(|(start, _)| *start <= offset)
+            .map_or(offset, |(start, chapter)| chapter + offset - start)
+    }
+
+    // Offsets of the content starting at the offset.
+    fn slice(&self, offset: usize) -> Self {
+        let mut lines = vec![(0, self.get(offset))];
+        lines.extend(
+            self.0
+                .iter()
+                .filter(|(start, _)| *start > offset)
+                .map(|(start, chapter)| (start - offset, *chapter)),
+        );
+
+        Offsets(lines)
+    }
+}
+
+// Indentation of the content of a list item containing the marker at the offset, e.g. `   ` for
+// markers after `1. ` or on a later line indented by three spaces. Markers in other containers
+// (e.g. block quotes) or after other text have no indentation.
+fn line_indent(content: &str, offset: usize) -> String {
+    let line_start = content[..offset].rfind('\n').map_or(0, |index| index + 1);
+    let prefix = &content[line_start..offset];
+
+    let trimmed = prefix.trim_start_matches([' ', '\t']);
+    let item = trimmed
+        .strip_prefix(['-', '*', '+'])
+        .or_else(|| {
+            trimmed
+                .trim_start_matches(|c: char| c.is_ascii_digit())
+                .strip_prefix(['.', ')'])
+                .filter(|_| trimmed.starts_with(|c: char| c.is_ascii_digit()))
+        })
+        .filter(|rest| rest.starts_with([' ', '\t']) && rest.trim().is_empty());
+
+    match (trimmed.is_empty(), item) {
+        (true, _) => prefix.to_string(),
+        (false, Some(_)) if !prefix.contains('\t') => " ".repeat(prefix.len()),
+        _ => "".to_string(),
+    }
+}
+
+// Removes the indentation from the lines of the content at the span, and returns the dedented
+// content with its offsets in the chapter.
+fn dedent(content: &str, span: Range<usize>, indent: &str, offsets: &Offsets) -> (String, Offsets) {
+    let mut dedented = String::with_capacity(span.len());
+    let mut lines = vec![(0, offsets.get(span.start))];
+    let mut start = span.start;
+
+    for line in content[span].split_inclusive('\n') {
+        let trimmed = line
+            .strip_prefix(indent)
+            .unwrap_or_else(|| line.trim_start_matches([' ', '\t']));
+
+        lines.push((
+            dedented.len(),
+            offsets.get(start + line.len() - trimmed.len()),
+        ));
+        dedented.push_str(trimmed);
+        start += line.len();
+    }
+
+    (dedented, Offsets(lines))
+}
+
+// The first line follows the indentation before the marker, blank lines are not indented.
+fn indent_lines(content: &str, indent: &str) -> String {
+    if indent.is_empty() {
+        return content.to_string();
+    }
+
+    content
+        .split_inclusive('\n')
+        .enumerate()
+        .map(|(index, line)| match index > 0 && !line.trim().is_empty() {
+            true => format!("{}{}", indent, line),
+            false => line.to_string(),
+        })
+        .collect()
+}
+
+// Removes blank lines at the end and ends the content with a single newline. Only whitespace
+// after the last line with content is removed, so trailing spaces of that line are kept.
+fn trim_trailing_lines(content: &str) -> String {
+    let Some(last) = content.rfind(|c: char| !c.is_whitespace()) else {
+        return String::new();
+    };
+    let end = content[last..]
+        .find('\n')
+        .map_or(content.len(), |index| last + index);
+
+    format!("{}\n", &content[..end])
+}
+
+fn process_content(ctx: &mut ChapterContext, content: &str, offsets: &Offsets) -> Result<String> {
+    let configs = parse_tabs(content, ctx.config, &ctx.markers).map_err(|error| {
+        match error.downcast_ref::<BlockParseError>() {
+            Some(block_error) => {
+                let location = ctx.location(offsets.get(block_error.span().start));
+                error.context(format!("Failed to parse tabs at {}.", location))
+            }
+            None => error,
+        }
+    })?;
+
+    // Unclosed tabs would swallow the rest of the content, e.g. when the end marker is in another
+    // included file, so the content is left untouched instead of rendering broken tabs.
+    if let Some((span, _)) = configs.iter().find(|(_, tabs_config)| !tabs_config.closed) {
+        warn!(
+            "Tabs at {} are not closed, rendering the content without tabs.",
+            ctx.location(offsets.get(span.start))
+        );
+        return Ok(content.to_string());
+    }
+
+    let mut processed = String::with_capacity(content.len());
+    let mut offset: usize = 0;
+
+    for (span, tabs_config) in configs {
+        push_gap(ctx, &mut processed, content, offsets, offset..span.start);
+        offset = span.end;
+
+        // Tabs in list items are indented, so they are dedented and parsed again before processing,
+        // and the rendered tabs are indented to stay in the list item.
+        let indent = line_indent(content, span.start);
+        if indent.is_empty() {
+            processed.push_str(&process_group(ctx, content, offsets, span, tabs_config)?);
+            continue;
+        }
+
+        let (block, block_offsets) = dedent(content, span.clone(), &indent, offsets);
+        let Some((block_span, block_config)) = parse_tabs(&block, ctx.config, &ctx.markers)?
+            .into_iter()
+            .next()
+        else {
+            bail!(
+                "Failed to parse dedented tabs at {}.",
+                ctx.location(offsets.get(span.start))
+            );
+        };
+
+        let group = process_group(ctx, &block, &block_offsets, block_span, block_config)?;
+        processed.push_str(&indent_lines(&group, &indent));
+    }
+
+    push_gap(ctx, &mut processed, content, offsets, offset..content.len());
+
+    Ok(processed)
+}
+
+// Renders the tabs at the span of the content, or their content if they are not rendered as tabs.
+fn process_group(
+    ctx: &mut ChapterContext,
+    content: &str,
+    offsets: &Offsets,
+    span: Range<usize>,
+    mut tabs_config: TabsConfig,
+) -> Result<String> {
+    let mut processed = String::new();
+
+    if let Some(from) = &tabs_config.from {
+        if !tabs_config.tabs.is_empty() {
+            warn!(
+                "Tabs at {} are read from `{}`, the tabs in its content are ignored.",
+                ctx.location(offsets.get(span.start)),
+                from
+            );
+        }
+
+        tabs_config.tabs = data_tabs(ctx, from, offsets.get(span.start))?
+            .into_iter()
+            .map(|(mut tab, tab_content)| {
+                tab.content_span = span.start..span.start;
+                (tab, tab_content)
+            })
+            .collect();
+        ctx.reads_files = true;
+    }
+
+    if tabs_config.tabs.is_empty() {
+        warn!(
+            "Tabs at {} do not contain any tab, rendering its content without tabs.",
+            ctx.location(offsets.get(span.start))
+        );
+
+        let content_span = tabs_config.content_span.clone();
+        processed.push_str(&process_content(
+            ctx,
+            &content[content_span.clone()],
+            &offsets.slice(content_span.start),
+        )?);
+        return Ok(processed);
+    }
+
+    trace!(
+        "Tabs at {} ({:?}) with {} tabs {:?}.",
+        ctx.location(offsets.get(span.start)),
+        offsets.get(span.start)..offsets.get(span.end),
+        tabs_config.tabs.len(),
+        tabs_config
+            .tabs
+            .iter()
+            .map(|(tab, _)| tab.name.as_str())
+            .collect::<Vec<_>>()
+    );
+
+    ctx.report.push(TabsReport {
+        chapter: ctx.path.clone(),
+        span: offsets.get(span.start)..offsets.get(span.end),
+        labels: tabs_config
+            .tabs
+            .iter()
+            .map(|(tab, _)| tab.name.clone())
+            .collect(),
+    });
+
+    if let Some(offset) = marker_between_tabs(content, &tabs_config, &ctx.markers) {
+        warn!(
+            "Tabs marker at {} is not on its own line and is ignored, separate adjacent tabs with a new line.",
+            ctx.location(offsets.get(offset))
+        );
+    }
+
+    // Content before the first tab is shared by all tabs, so it is rendered above them.
+    let preamble_span = tabs_config.preamble_span.clone();
+    if !content[preamble_span.clone()].trim().is_empty() {
+        processed.push_str(&process_content(
+            ctx,
+            &content[preamble_span.clone()],
+            &offsets.slice(preamble_span.start),
+        )?);
+        processed.push_str("\n\n");
+    }
+
+    if tabs_config.tabs.len() == 1 && ctx.config.collapse_single {
+        let (tab, tab_content) = &tabs_config.tabs[0];
+        processed.push_str(&render_tab(ctx, tab, tab_content, offsets)?);
+        return Ok(processed);
+    }
+
+    if tabs_config.tabs.len() == 1 {
+        warn!(
+            "Tabs at {} contain only one tab.",
+            ctx.location(offsets.get(span.start))
+        );
+    }
+
+    for name in order_tabs(&mut tabs_config) {
+        warn!(
+            "Tab `{}` in the order of tabs at {} does not exist.",
+            name,
+            ctx.location(offsets.get(span.start))
+        );
+    }
+
+    for name in duplicate_names(&tabs_config) {
+        warn!(
+            "Tabs at {} contain multiple tabs named `{}`.",
+            ctx.location(offsets.get(span.start)),
+            name
+        );
+    }
+
+    // Only the selected tab is rendered, without tab buttons, e.g. for printed outputs.
+    if let Some(static_select) = &ctx.config.static_select {
+        let index = tabs_config
+            .tabs
+            .iter()
+            .position(|(tab, _)| &tab.name == static_select)
+            .unwrap_or_else(|| active_index(&tabs_config));
+        let (tab, tab_content) = &tabs_config.tabs[index];
+        processed.push_str(&render_tab(ctx, tab, tab_content, offsets)?);
+        return Ok(processed);
+    }
+
+    let tabs_group = ctx.group_id();
+    ctx.group += 1;
+
+    ctx.tab_depth += 1;
+    for (tab, tab_content) in &mut tabs_config.tabs {
+        *tab_content = render_tab(ctx, tab, tab_content, offsets)?;
+    }
+    ctx.tab_depth -= 1;
+
+    if ctx.config.debug_comments && ctx.config.is_html_renderer() {
+        processed.push_str(&format!(
+            "<!-- tabs @ {} -->\n",
+            ctx.location(offsets.get(span.start))
+        ));
+    }
+
+    processed.push_str(&match ctx.config.is_html_renderer() {
+        true => tabs(&tabs_config, ctx.config, &tabs_group),
+        false => sections(&tabs_config),
+    });
+
+    Ok(processed)
+}
+
+// Renders the content of a tab, at the offsets of the content of its tabs. Collapsed and statically
+// selected tabs are rendered like the tabs of a group, so the output does not depend on how a tab
+// is shown.
+fn render_tab(
+    ctx: &mut ChapterContext,
+    tab: &TabConfig,
+    tab_content: &str,
+    offsets: &Offsets,
+) -> Result<String> {
+    if tab.raw {
+        return Ok(raw(&tab_content.replace("\r\n", "\n"), ctx.config));
+    }
+
+    let rendered = process_content(ctx, tab_content, &offsets.slice(tab.content_span.start))?
+        .replace("\r\n", "\n");
+
+    Ok(match ctx.config.trim_trailing_lines {
+        true => trim_trailing_lines(&rendered),
+        false => rendered,
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use std::{env, fs, process};
+
+    use test_log::test;
+
+    use super::*;
+    use crate::stats::ChapterStats;
+
+    #[test]
+    fn test_process_content_nested() -> Result<()> {
+        let content = "\
+        {{#tabs }}\n\
+        {{#tab name=\"Linux\" }}\n\
+        {{#tabs }}\n\
+        {{#tab name=\"apt\" }}\n\
+        apt install example\n\
+        {{#endtab }}\n\
+        {{#tab name=\"dnf\" }}\n\
+        {{#tabs }}\n\
+        {{#tab name=\"dnf4\" }}\n\
+        dnf4 install example\n\
+        {{#endtab }}\n\
+        {{#endtabs }}\n\
+        {{#endtab }}\n\
+        {{#endtabs }}\n\
+        {{#endtab }}\n\
+        {{#tab name=\"macOS\" }}\n\
+        brew install example\n\
+        {{#endtab }}\n\
+        {{#endtabs }}\n\
+        \n\
+        {{#tabs }}\n\
+        {{#tab name=\"Linux\" }}\n\
+        Other content.\n\
+        {{#endtab }}\n\
+        {{#endtabs }}";
+
+        let config = PreprocessorConfig::default();
+        let actual = process_content(
+            &mut ChapterContext::new(&config, "test.md".to_string(), content),
+            content,
+            &Offsets::default(),
+        )?;
+
+        assert!(!actual.contains("{{#"));
+        assert_eq!(
+            4,
+            actual
+                .matches("<div class=\"mdbook-tabs-container\">")
+                .count()
+        );
+
+        for id in [
+            "mdbook-tab-0-linux",
+            "mdbook-tab-0-macos",
+            "mdbook-tab-1-apt",
+            "mdbook-tab-1-dnf",
+            "mdbook-tab-2-dnf4",
+            "mdbook-tab-3-linux",
+        ] {
+            assert_eq!(1, actual.matches(&format!("id=\"{}\"", id)).count());
+        }
+
+        let outer = actual.find("id=\"mdbook-tab-content-0-linux\"").unwrap();
+        let inner = actual.find("id=\"mdbook-tab-content-1-dnf\"").unwrap();
+        let innermost = actual.find("dnf4 install example").unwrap();
+        let outer_end = actual.find("id=\"mdbook-tab-content-0-macos\"").unwrap();
+        assert!(outer < inner && inner < innermost && innermost < outer_end);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_process_content_without_tab() -> Result<()> {
+        let content = "\
+        Before.\n\
+        \n\
+        {{#tabs }}\n\
+        Some content without tabs.\n\
+        {{#endtabs }}\n\
+        \n\
+        After.";
+
+        let config = PreprocessorConfig::default();
+        let actual = process_content(
+            &mut ChapterContext::new(&config, "test.md".to_string(), content),
+            content,
+            &Offsets::default(),
+        )?;
+
+        assert_eq!(
+            "Before.\n\n\nSome content without tabs.\n\n\nAfter.",
+            actual
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_process_content_code_block() -> Result<()> {
+        let content = "\
+        ```md\n\
+        {{#tabs }}\n\
+        {{#tab name=\"Linux\" }}\n\
+        Example.\n\
+        {{#endtab }}\n\
+        {{#endtabs }}\n\
+        ```\n\
+        \n\
+        {{#tabs }}\n\
+        {{#tab name=\"Markdown\" }}\n\
+        \n\
+        ```md\n\
+        {{#tabs }}\n\
+        ```\n\
+        \n\
+        {{#endtab }}\n\
+        {{#tab name=\"HTML\" }}\n\
+        HTML.\n\
+        {{#endtab }}\n\
+        {{#endtabs }}";
+
+        let config = PreprocessorConfig::default();
+        let actual = process_content(
+            &mut ChapterContext::new(&config, "test.md".to_string(), content),
+            content,
+            &Offsets::default(),
+        )?;
+
+        assert!(actual.starts_with(
+            "```md\n{{#tabs }}\n{{#tab name=\"Linux\" }}\nExample.\n{{#endtab }}\n{{#endtabs }}\n```\n"
+        ));
+        assert_eq!(
+            1,
+            actual
+                .matches("<div class=\"mdbook-tabs-container\">")
+                .count()
+        );
+        assert!(actual.contains("id=\"mdbook-tab-0-markdown\""));
+        assert!(actual.contains("id=\"mdbook-tab-0-html\""));
+        assert!(actual.contains("```md\n{{#tabs }}\n```"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_process_content_collapse_single() -> Result<()> {
+        let content = "\
+        Before.\n\
+        \n\
+        {{#tabs }}\n\
+        {{#tab name=\"Linux\" }}\n\
+        Install with `apt`.\n\
+        {{#endtab }}\n\
+        {{#endtabs }}\n\
+        \n\
+        {{#tabs }}\n\
+        {{#tab name=\"Linux\" }}\n\
+        Linux.\n\
+        {{#endtab }}\n\
+        {{#tab name=\"macOS\" }}\n\
+        macOS.\n\
+        {{#endtab }}\n\
+        {{#endtabs }}";
+
+        let config = PreprocessorConfig {
+            collapse_single: true,
+            ..Default::default()
+        };
+        let actual = process_content(
+            &mut ChapterContext::new(&config, "test.md".to_string(), content),
+            content,
+            &Offsets::default(),
+        )?;
+
+        assert!(actual.starts_with("Before.\n\n\nInstall with `apt`.\n\n\n<div"));
+        assert_eq!(
+            1,
+            actual
+                .matches("<div class=\"mdbook-tabs-container\">")
+                .count()
+        );
+        assert!(actual.contains("id=\"mdbook-tab-0-linux\""));
+
+        let content = "\
+        {{#tabs }}\n\
+        {{#tab name=\"Config\",raw=true }}\n\
+        **Not** bold & not {{#tabs }}.\n\
+        {{#endtab }}\n\
+        {{#endtabs }}";
+        let actual = process_content(
+            &mut ChapterContext::new(&config, "test.md".to_string(), content),
+            content,
+            &Offsets::default(),
+        )?;
+
+        assert_eq!(
+            "<pre class=\"mdbook-tab-raw\"><code>**Not** bold &amp; not {{#tabs }}.\n</code></pre>\n",
+            actual
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_process_content_adjacent_tabs() -> Result<()> {
+        let content = "\
+        {{#tabs }}\n\
+        {{#tab name=\"Linux\" }}\n\
+        Linux.\n\
+        {{#endtab }}\n\
+        {{#endtabs }}\n\
+        {{#tabs }}\n\
+        {{#tab name=\"macOS\" }}\n\
+        macOS.\n\
+        {{#endtab }}\n\
+        {{#endtabs }}\n";
+
+        let config = PreprocessorConfig::default();
+        let configs = parse_tabs(content, &config, &Markers::new(&config))?;
+        assert_eq!(2, configs.len());
+        for (_, tabs_config) in &configs {
+            assert_eq!(
+                None,
+                marker_between_tabs(content, tabs_config, &Markers::new(&config))
+            );
+        }
+
+        let mut ctx = ChapterContext::new(&config, "test.md".to_string(), content);
+        let actual = process_content(&mut ctx, content, &Offsets::default())?;
+
+        assert_eq!(2, ctx.group);
+        let (first, second) = actual
+            .split_once("</div>\n<div class=\"mdbook-tabs-container\">")
+            .unwrap_or_default();
+        assert!(first.contains("id=\"mdbook-tab-0-linux\""));
+        assert!(!first.contains("macOS"));
+        assert!(second.contains("id=\"mdbook-tab-1-macos\""));
+        assert!(!second.contains("Linux"));
+
+        // Markers on one line are not matched, so the warning points at them.
+        let content = content.replace("{{#endtabs }}\n{{#tabs }}", "{{#endtabs }}{{#tabs }}");
+        let configs = parse_tabs(&content, &config, &Markers::new(&config))?;
+        assert_eq!(1, configs.len());
+        assert_eq!(
+            content.find("{{#endtabs }}{{#tabs }}"),
+            marker_between_tabs(&content, &configs[0].1, &Markers::new(&config))
+        );
+
+        Ok(())
+    }
+
+    // Values of an attribute in the elements with a role, in the order of the output.
+    fn attributes<'a>(html: &'a str, role: &str, name: &str) -> Vec<&'a str> {
+        html.split('<')
+            .filter(|element| element.contains(&format!(" role=\"{}\"", role)))
+            .filter_map(|element| element.split_once(&format!(" {}=\"", name)))
+            .filter_map(|(_, value)| value.split_once('"'))
+            .map(|(value, _)| value)
+            .collect()
+    }
+
+    #[test]
+    fn test_process_content_aria_ids() -> Result<()> {
+        let content = "\
+        {{#tabs }}\n\
+        {{#tab name=\"Linux\" }}\n\
+        {{#tabs }}\n\
+        {{#tab name=\"apt\" }}\n\
+        apt install example\n\
+        {{#endtab }}\n\
+        {{#tab name=\"dnf\" }}\n\
+        dnf install example\n\
+        {{#endtab }}\n\
+        {{#endtabs }}\n\
+        {{#endtab }}\n\
+        {{#tab name=\"macOS\" }}\n\
+        brew install example\n\
+        {{#endtab }}\n\
+        {{#tab name=\"Windows\" }}\n\
+        winget install example\n\
+        {{#endtab }}\n\
+        {{#endtabs }}\n";
+
+        for config in [
+            PreprocessorConfig::default(),
+            PreprocessorConfig {
+                pretty: true,
+                max_visible_tabs: Some(2),
+                id_strategy: IdStrategy::ChapterHash,
+                ..Default::default()
+            },
+            PreprocessorConfig {
+                lazy: true,
+                ..Default::default()
+            },
+        ] {
+            let mut ctx = ChapterContext::new(&config, "test.md".to_string(), content);
+            let actual = process_content(&mut ctx, content, &Offsets::default())?;
+
+            // The IDs are in the rendered HTML, so the links between tabs and tab contents do not
+            // depend on the JS.
+            let tab_ids = attributes(&actual, "tab", "id");
+            let controls = attributes(&actual, "tab", "aria-controls");
+            let panel_ids = attributes(&actual, "tabpanel", "id");
+            let labelled_by = attributes(&actual, "tabpanel", "aria-labelledby");
+
+            assert_eq!(5, tab_ids.len());
+            assert_eq!(tab_ids.len(), controls.len());
+            assert_eq!(tab_ids.len(), panel_ids.len());
+            assert_eq!(panel_ids.len(), labelled_by.len());
+
+            for (tab_id, control) in tab_ids.iter().zip(&controls) {
+                let index = panel_ids.iter().position(|id| id == control);
+                assert_eq!(Some(tab_id), index.map(|index| &labelled_by[index]));
+                assert_eq!(1, actual.matches(&format!("id=\"{}\"", tab_id)).count());
+                assert_eq!(1, actual.matches(&format!("id=\"{}\"", control)).count());
+            }
+
+            // Hidden lazy tab contents are in templates with the ID of the tab content.
+            let templates = actual
+                .split("<template data-tabcontent=\"")
+                .skip(1)
+                .filter_map(|template| template.split_once('"'))
+                .map(|(id, _)| id)
+                .collect::<Vec<_>>();
+            assert_eq!(if config.lazy { 3 } else { 0 }, templates.len());
+            assert!(templates.iter().all(|id| controls.contains(id)));
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_process_content_inline_code_marker() -> Result<()> {
+        let content = "\
+        {{#tabs }}\n\
+        {{#tab name=\"Markers\" }}\n\
+        Close a tab with `{{#endtab }}`\n\
+        and the tabs with `{{#endtabs }}`.\n\
+        \n\
+        ``{{#endtabs }}``\n\
+        {{#endtab }}\n\
+        {{#tab name=\"Colon\" }}\n\
+        Close the tabs with `:::`.\n\
+        {{#endtab }}\n\
+        {{#endtabs }}\n\
+        \n\
+        After.\n";
+
+        let config = PreprocessorConfig::default();
+        let configs = parse_tabs(content, &config, &Markers::new(&config))?;
+        assert_eq!(1, configs.len());
+        assert_eq!(
+            vec!["Markers", "Colon"],
+            configs[0]
+                .1
+                .tabs
+                .iter()
+                .map(|(tab, _)| tab.name.as_str())
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(
+            content
+                .rfind("{{#endtabs }}\n\nAfter.")
+                .map(|i| i + "{{#endtabs }}".len()),
+            Some(configs[0].0.end)
+        );
+        assert_eq!(None, inline_marker(content, &Markers::new(&config)));
+
+        let mut ctx = ChapterContext::new(&config, "test.md".to_string(), content);
+        let actual = process_content(&mut ctx, content, &Offsets::default())?;
+        assert!(actual.contains("Close a tab with `{{#endtab }}`\nand the tabs with `{{#endtabs }}`.\n\n``{{#endtabs }}``\n"));
+        assert!(actual.ends_with("</div>\n</div>\n</div>\n\nAfter.\n"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_process_content_list_item() -> Result<()> {
+        let content = "\
+        1. Install the package:\n\
+        \n\
+        \x20  {{#tabs }}\n\
+        \x20  {{#tab name=\"Linux\" }}\n\
+        \x20  ```shell\n\
+        \x20  apt install example\n\
+        \x20  ```\n\
+        \x20  {{#endtab }}\n\
+        \x20  {{#tab name=\"macOS\" }}\n\
+        \x20  - Run:\n\
+        \n\
+        \x20        brew install example\n\
+        \n\
+        \x20  {{#endtab }}\n\
+        \x20  {{#endtabs }}\n\
+        \n\
+        2. {{#tabs }}\n\
+        \x20  {{#tab name=\"Linux\" }}\n\
+        \x20  Run `example`.\n\
+        \x20  {{#endtab }}\n\
+        \x20  {{#tab name=\"macOS\" }}\n\
+        \x20  Open the app.\n\
+        \x20  {{#endtab }}\n\
+        \x20  {{#endtabs }}\n\
+        \n\
+        3. Done.\n";
+
+        let config = PreprocessorConfig::default();
+        let mut ctx = ChapterContext::new(&config, "test.md".to_string(), content);
+        let actual = process_content(&mut ctx, content, &Offsets::default())?;
+
+        let mut html = String::new();
+        pulldown_cmark::html::push_html(&mut html, Parser::new(&actual));
+
+        // The tabs stay in their list items, so the list is not split by the tabs.
+        assert_eq!(1, html.matches("<ol>").count());
+        assert!(!html.contains("<ol start="));
+        assert!(html.starts_with(
+            "<ol>\n<li>\n<p>Install the package:</p>\n<div class=\"mdbook-tabs-container\">\n"
+        ));
+        assert!(html.contains("</div>\n</li>\n<li><div class=\"mdbook-tabs-container\">\n"));
+        assert!(html.ends_with("</div>\n</li>\n<li>\n<p>Done.</p>\n</li>\n</ol>\n"));
+
+        // Tab contents are dedented, so code blocks keep their content.
+        assert!(
+            html.contains("<pre><code class=\"language-shell\">apt install example\n</code></pre>")
+        );
+        assert!(html
+            .contains("<li>\n<p>Run:</p>\n<pre><code>brew install example\n</code></pre>\n</li>"));
+        assert!(html.contains("<p>Run <code>example</code>.</p>"));
+
+        assert_eq!("", line_indent("Text {{#tabs }}", 5));
+        assert_eq!("", line_indent("> {{#tabs }}", 2));
+        assert_eq!("  ", line_indent("- {{#tabs }}", 2));
+        assert_eq!("    ", line_indent("10) {{#tabs }}", 4));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_process_content_list_item_location() -> Result<()> {
+        let content = "\
+        1. Install the package:\n\
+        \n\
+        \x20  {{#tabs }}\n\
+        \x20  {{#tab name=\"Linux\" }}\n\
+        \x20  Use a package manager:\n\
+        \n\
+        \x20  {{#tabs }}\n\
+        \x20  {{#tab name=\"apt\" }}\n\
+        \x20  apt install example\n\
+        \x20  {{#endtab }}\n\
+        \x20  {{#tab name=\"dnf\" }}\n\
+        \x20  dnf install example\n\
+        \x20  {{#endtab }}\n\
+        \x20  {{#endtabs }}\n\
+        \x20  {{#endtab }}\n\
+        \x20  {{#tab name=\"macOS\" }}\n\
+        \x20  brew install example\n\
+        \x20  {{#endtab }}\n\
+        \x20  {{#endtabs }}\n";
+
+        let config = PreprocessorConfig {
+            debug_comments: true,
+            ..Default::default()
+        };
+        let mut ctx = ChapterContext::new(&config, "test.md".to_string(), content);
+        let actual = process_content(&mut ctx, content, &Offsets::default())?;
+
+        // Locations of nested tabs are in the chapter, not in the dedented content of the tab.
+        assert!(actual.contains("<!-- tabs @ test.md:3:4 -->"));
+        assert!(actual.contains("<!-- tabs @ test.md:7:4 -->"));
+
+        let start = content.rfind("{{#tabs }}").unwrap();
+        let end = content.find("{{#endtabs }}").unwrap() + "{{#endtabs }}".len();
+        assert_eq!(start..end, ctx.report[1].span);
+        assert_eq!(vec!["apt", "dnf"], ctx.report[1].labels);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_process_content_static_select() -> Result<()> {
+        let content = "\
+        {{#tabs }}\n\
+        {{#tab name=\"Linux\" }}\n\
+        Install on Linux.\n\
+        {{#endtab }}\n\
+        {{#tab name=\"macOS\" }}\n\
+        Install on macOS.\n\
+        {{#endtab }}\n\
+        {{#endtabs }}\n\
+        \n\
+        {{#tabs default=\"Cargo\" }}\n\
+        {{#tab name=\"Binary\" }}\n\
+        Download the binary.\n\
+        {{#endtab }}\n\
+        {{#tab name=\"Cargo\" }}\n\
+        Build with Cargo.\n\
+        {{#endtab }}\n\
+        {{#endtabs }}\n\
+        \n\
+        {{#tabs }}\n\
+        {{#tab name=\"Windows\" }}\n\
+        Install on Windows.\n\
+        {{#endtab }}\n\
+        {{#tab name=\"Docker\" }}\n\
+        Run in Docker.\n\
+        {{#endtab }}\n\
+        {{#endtabs }}\n";
+
+        let config = PreprocessorConfig {
+            static_select: Some("macOS".to_string()),
+            ..Default::default()
+        };
+        let mut ctx = ChapterContext::new(&config, "test.md".to_string(), content);
+        let actual = process_content(&mut ctx, content, &Offsets::default())?;
+
+        assert_eq!(
+            "\nInstall on macOS.\n\n\n\nBuild with Cargo.\n\n\n\nInstall on Windows.\n\n",
+            actual
+        );
+        assert!(!actual.contains("mdbook-tab"));
+        assert_eq!(0, ctx.group);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_process_content_empty_tab() -> Result<()> {
+        let marker = "\
+        {{#tabs }}\n\
+        {{#tab name=\"Linux\" }}\n\
+        \n\
+        {{#endtab }}\n\
+        {{#tab name=\"macOS\" }}\n\
+        macOS.\n\
+        {{#endtab }}\n\
+        {{#endtabs }}";
+        let heading = "\
+        {{#tabs }}\n\
+        \n\
+        #### Linux\n\
+        \n\
+        Linux.\n\
+        \n\
+        #### macOS\n\
+        \n\
+        {{#endtabs }}";
+
+        for (content, heading_level, empty) in
+            [(marker, None, "linux"), (heading, Some(4), "macos")]
+        {
+            let config = PreprocessorConfig {
+                heading_level,
+                empty_placeholder: Some("(no content)".to_string()),
+                ..Default::default()
+            };
+            let actual = process_content(
+                &mut ChapterContext::new(&config, "test.md".to_string(), content),
+                content,
+                &Offsets::default(),
+            )?;
+
+            assert_eq!(2, actual.matches("role=\"tabpanel\"").count());
+            assert!(actual.contains(&format!(
+                "aria-labelledby=\"mdbook-tab-0-{}\">\n\n<p class=\"mdbook-tab-empty\">(no content)</p>\n\n</div>",
+                empty
+            )));
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_process_content_unclosed() -> Result<()> {
+        // The end marker is in an included file which is not resolved yet.
+        let content = "\
+        Intro.\n\
+        \n\
+        {{#tabs }}\n\
+        {{#tab name=\"Linux\" }}\n\
+        Linux.\n\
+        {{#endtab }}\n\
+        {{#include end.md}}\n";
+
+        let config = PreprocessorConfig::default();
+        let mut ctx = ChapterContext::new(&config, "test.md".to_string(), content);
+        let actual = process_content(&mut ctx, content, &Offsets::default())?;
+
+        assert_eq!(content, actual);
+        assert_eq!(0, ctx.group);
+        assert!(ctx.report.is_empty());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_process_content_trim_trailing_lines() -> Result<()> {
+        let content = "\
+        {{#tabs }}\n\
+        \n\
+        #### Linux\n\
+        \n\
+        Linux.\n\
+        \n\
+        \n\
+        \n\
+        #### macOS\n\
+        \n\
+        macOS.\n\
+        \n\
+        \t\n\
+        \n\
+        #### Windows\n\
+        \n\
+        ```shell\n\
+        winget install example\n\
+        \n\
+        ```\n\
+        {{#endtabs }}";
+
+        let config = PreprocessorConfig {
+            heading_level: Some(4),
+            ..Default::default()
+        };
+        let actual = process_content(
+            &mut ChapterContext::new(&config, "test.md".to_string(), content),
+            content,
+            &Offsets::default(),
+        )?;
+
+        for (slug, tab_content) in [
+            ("linux", "\nLinux.\n"),
+            ("macos", "\nmacOS.\n"),
+            ("windows", "\n```shell\nwinget install example\n\n```\n"),
+        ] {
+            assert!(actual.contains(&format!(
+                "aria-labelledby=\"mdbook-tab-0-{}\">\n\n{}\n\n</div>",
+                slug, tab_content
+            )));
+        }
+
+        let config = PreprocessorConfig {
+            heading_level: Some(4),
+            trim_trailing_lines: false,
+            ..Default::default()
+        };
+        let actual = process_content(
+            &mut ChapterContext::new(&config, "test.md".to_string(), content),
+            content,
+            &Offsets::default(),
+        )?;
+
+        assert!(actual
+            .contains("aria-labelledby=\"mdbook-tab-0-linux\">\n\n\nLinux.\n\n\n\n\n\n</div>"));
+        assert!(actual
+            .contains("aria-labelledby=\"mdbook-tab-0-macos\">\n\n\nmacOS.\n\n\t\n\n\n\n</div>"));
+
+        // Collapsed and selected tabs are post-processed like the tabs of a group.
+        let content = "\
+        {{#tabs }}\r\n\
+        {{#tab name=\"Linux\" }}\r\n\
+        Linux.\r\n\
+        \r\n\
+        \r\n\
+        {{#endtab }}\r\n\
+        {{#endtabs }}";
+
+        for config in [
+            PreprocessorConfig {
+                collapse_single: true,
+                ..Default::default()
+            },
+            PreprocessorConfig {
+                static_select: Some("Linux".to_string()),
+                ..Default::default()
+            },
+        ] {
+            let actual = process_content(
+                &mut ChapterContext::new(&config, "test.md".to_string(), content),
+                content,
+                &Offsets::default(),
+            )?;
+
+            assert_eq!("\nLinux.\n", actual);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_trim_trailing_lines() {
+        assert_eq!("Text.\n", trim_trailing_lines("Text."));
+        assert_eq!("Text.\n", trim_trailing_lines("Text.\n"));
+        assert_eq!("Text.\n", trim_trailing_lines("Text.\n\n \n\t\n"));
+        assert_eq!("\nText. \n", trim_trailing_lines("\nText. \n  "));
+        assert_eq!(
+            "```\ncode\n\n```\n",
+            trim_trailing_lines("```\ncode\n\n```\n\n")
+        );
+        assert_eq!("", trim_trailing_lines("\n \n"));
+    }
+
+    #[test]
+    fn test_process_content_raw() -> Result<()> {
+        let content = "\
+        {{#tabs }}\n\
+        \n\
+        ### Markdown\n\
+        \n\
+        **Bold** text.\n\
+        \n\
+        ### Config {raw}\n\
+        \n\
+        {{#include config.toml}}\n\
+        \n\
+        {{#tab name=\"Literal\" }}\n\
+        <b>Not</b> **bold** & not tabs.\n\
+        \n\
+        {{#endtabs }}";
+
+        let config = PreprocessorConfig {
+            heading_level: Some(3),
+            ..Default::default()
+        };
+        let actual = process_content(
+            &mut ChapterContext::new(&config, "test.md".to_string(), content),
+            content,
+            &Offsets::default(),
+        )?;
+
+        assert_eq!(1, actual.matches("mdbook-tabs-container").count());
+        assert!(actual.contains("data-tabname=\"Config\""));
+        assert!(actual.contains(
+            "<pre class=\"mdbook-tab-raw\"><code>{{#include config.toml}}\n\n{{#tab name=&quot;Literal&quot; }}\n&lt;b&gt;Not&lt;/b&gt; **bold** &amp; not tabs.\n</code></pre>"
+        ));
+        assert!(actual.contains("\n\n**Bold** text.\n"));
+
+        let config = PreprocessorConfig {
+            heading_level: Some(3),
+            renderer: "markdown".to_string(),
+            ..Default::default()
+        };
+        let actual = process_content(
+            &mut ChapterContext::new(&config, "test.md".to_string(), content),
+            content,
+            &Offsets::default(),
+        )?;
+
+        assert!(actual.contains(
+            "### Config\n\n```text\n{{#include config.toml}}\n\n{{#tab name=\"Literal\" }}\n<b>Not</b> **bold** & not tabs.\n```"
+        ));
+
+        let content = "\
+        {{#tabs }}\n\
+        {{#tab name=\"Config\",raw=true }}\n\
+        **Raw.**\n\
+        {{#endtab }}\n\
+        {{#endtabs }}";
+        let config = PreprocessorConfig::default();
+        let actual = process_content(
+            &mut ChapterContext::new(&config, "test.md".to_string(), content),
+            content,
+            &Offsets::default(),
+        )?;
+
+        assert!(actual.contains("<pre class=\"mdbook-tab-raw\"><code>**Raw.**\n</code></pre>"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_process_content_debug_comments() -> Result<()> {
+        let content = "\
+        Before.\n\
+        \n\
+        {{#tabs }}\n\
+        {{#tab name=\"Linux\" }}\n\
+        {{#tabs }}\n\
+        {{#tab name=\"apt\" }}\n\
+        apt install example\n\
+        {{#endtab }}\n\
+        {{#endtabs }}\n\
+        {{#endtab }}\n\
+        {{#endtabs }}";
+
+        let config = PreprocessorConfig {
+            debug_comments: true,
+            ..Default::default()
+        };
+        let actual = process_content(
+            &mut ChapterContext::new(&config, "test.md".to_string(), content),
+            content,
+            &Offsets::default(),
+        )?;
+
+        assert!(actual.starts_with("Before.\n\n<!-- tabs @ test.md:3:1 -->\n<div"));
+        assert!(actual.contains("\n<!-- tabs @ test.md:5:1 -->\n<div"));
+
+        let actual = process_content(
+            &mut ChapterContext::new(
+                &PreprocessorConfig::default(),
+                "test.md".to_string(),
+                content,
+            ),
+            content,
+            &Offsets::default(),
+        )?;
+
+        assert!(!actual.contains("<!--"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_process_content_id_strategy() -> Result<()> {
+        let content = "\
+        {{#tabs }}\n\
+        {{#tab name=\"Linux\" }}\n\
+        Linux.\n\
+        {{#endtab }}\n\
+        {{#endtabs }}";
+
+        let config = PreprocessorConfig {
+            id_strategy: IdStrategy::ChapterHash,
+            ..Default::default()
+        };
+        let first = process_content(
+            &mut ChapterContext::new(&config, "first.md".to_string(), content),
+            content,
+            &Offsets::default(),
+        )?;
+        let second = process_content(
+            &mut ChapterContext::new(&config, "second.md".to_string(), content),
+            content,
+            &Offsets::default(),
+        )?;
+
+        let id = format!("mdbook-tab-{}-0-linux", path_hash("first.md"));
+        assert!(first.contains(&format!("id=\"{}\"", id)));
+        assert!(first.contains(&format!("aria-labelledby=\"{}\"", id)));
+        assert!(!second.contains(&id));
+        assert_eq!(path_hash("guide/first.md"), path_hash("guide\\first.md"));
+        assert_eq!("811c9dc5", path_hash(""));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_process_content_preamble() -> Result<()> {
+        let marker = "\
+        {{#tabs }}\n\
+        Install the **example** package.\n\
+        \n\
+        {{#tab name=\"Linux\" }}\n\
+        Linux.\n\
+        {{#endtab }}\n\
+        {{#tab name=\"macOS\" }}\n\
+        macOS.\n\
+        {{#endtab }}\n\
+        {{#endtabs }}";
+        let heading = "\
+        {{#tabs }}\n\
+        \n\
+        Install the **example** package.\n\
+        \n\
+        #### Linux\n\
+        \n\
+        Linux.\n\
+        \n\
+        #### macOS\n\
+        \n\
+        macOS.\n\
+        \n\
+        {{#endtabs }}";
+
+        for (content, heading_level) in [(marker, None), (heading, Some(4))] {
+            let config = PreprocessorConfig {
+                heading_level,
+                ..Default::default()
+            };
+            let actual = process_content(
+                &mut ChapterContext::new(&config, "test.md".to_string(), content),
+                content,
+                &Offsets::default(),
+            )?;
+
+            let preamble = actual.find("Install the **example** package.").unwrap();
+            let tabs = actual
+                .find("<div class=\"mdbook-tabs-container\">")
+                .unwrap();
+            assert!(preamble < tabs);
+            assert_eq!(
+                1,
+                actual.matches("Install the **example** package.").count()
+            );
+            assert!(actual.contains("aria-labelledby=\"mdbook-tab-0-linux\">\n\n\nLinux.\n"));
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_process_content_code_blocks() -> Result<()> {
+        let content = "\
+        {{#tabs }}\n\
+        {{#tab name=\"Rust\" }}\n\
+        \n\
+        ```rust,ignore\n\
+        // src/main.rs\n\
+        fn main() {}\n\
+        ```\n\
+        ```toml\n\
+        # Cargo.toml\n\
+        [package]\n\
+        ```\n\
+        \n\
+        {{#endtab }}\n\
+        {{#tab name=\"Python\" }}\n\
+        Python.\n\
+        {{#endtab }}\n\
+        {{#endtabs }}";
+
+        let config = PreprocessorConfig::default();
+        let actual = process_content(
+            &mut ChapterContext::new(&config, "test.md".to_string(), content),
+            content,
+            &Offsets::default(),
+        )?;
+
+        let mut html = String::new();
+        pulldown_cmark::html::push_html(&mut html, Parser::new_ext(&actual, options()));
+
+        let panel_start = html.find("id=\"mdbook-tab-content-0-rust\"").unwrap();
+        let panel = &html[panel_start..html[panel_start..].find("</div>").unwrap() + panel_start];
+        assert_eq!(2, panel.matches("<pre>").count());
+        assert!(panel.contains(
+            "<pre><code class=\"language-rust,ignore\">// src/main.rs\nfn main() {}\n</code></pre>"
+        ));
+        assert!(panel
+            .contains("<pre><code class=\"language-toml\"># Cargo.toml\n[package]\n</code></pre>"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_process_content_code_blocks_mdbook() -> Result<()> {
+        let content = "\
+        {{#tabs }}\n\
+        {{#tab name=\"Linux\" }}\n\
+        Linux.\n\
+        {{#endtab }}\n\
+        {{#tab name=\"macOS\" }}\n\
+        ```sh\n\
+        brew install example\n\
+        ```\n\
+        {{#endtab }}\n\
+        {{#endtabs }}";
+
+        for lazy in [false, true] {
+            let config = PreprocessorConfig {
+                lazy,
+                ..Default::default()
+            };
+            let actual = process_content(
+                &mut ChapterContext::new(&config, "test.md".to_string(), content),
+                content,
+                &Offsets::default(),
+            )?;
+
+            // mdBook's `book.js` highlights and adds copy buttons to `pre > code` elements.
+            let html = mdbook::utils::render_markdown(&actual, false);
+            let panel_start = html.find("id=\"mdbook-tab-content-0-macos\"").unwrap();
+            assert!(html[panel_start..]
+                .contains("<pre><code class=\"language-sh\">brew install example\n</code></pre>"));
+            assert_eq!(lazy, html[..panel_start].contains("<template"));
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_process_content_title() -> Result<()> {
+        let content = "\
+        {{#tabs title=\"Choose your OS\",global=\"os\" }}\n\
+        {{#tab name=\"Linux\" }}\n\
+        Linux.\n\
+        {{#endtab }}\n\
+        {{#tab name=\"macOS\" }}\n\
+        macOS.\n\
+        {{#endtab }}\n\
+        {{#endtabs }}";
+
+        let config = PreprocessorConfig::default();
+        let actual = process_content(
+            &mut ChapterContext::new(&config, "test.md".to_string(), content),
+            content,
+            &Offsets::default(),
+        )?;
+
+        assert!(actual.contains(
+            "<div class=\"mdbook-tabs-title\" id=\"mdbook-tabs-title-0\">Choose your OS</div>\n\
+            <nav class=\"mdbook-tabs\" role=\"tablist\" aria-labelledby=\"mdbook-tabs-title-0\">"
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_process_content_renderers() -> Result<()> {
+        let content = "\
+        {{#tabs }}\n\
+        {{#tab name=\"Linux\" }}\n\
+        Linux.\n\
+        {{#endtab }}\n\
+        {{#tab name=\"macOS\" }}\n\
+        macOS.\n\
+        {{#endtab }}\n\
+        {{#endtabs }}";
+
+        let book_config: mdbook::Config = "\
+        [preprocessor.tabs]\n\
+        renderers = [\"custom-html\"]\n"
+            .parse()?;
+
+        for (renderer, html) in [("html", true), ("custom-html", true), ("epub", false)] {
+            let config = PreprocessorConfig {
+                renderer: renderer.to_string(),
+                ..PreprocessorConfig::from_config(&book_config)?
+            };
+            let actual = process_content(
+                &mut ChapterContext::new(&config, "test.md".to_string(), content),
+                content,
+                &Offsets::default(),
+            )?;
+
+            assert_eq!(html, actual.contains("role=\"tablist\""), "{}", renderer);
+            assert_eq!(!html, actual.starts_with("### Linux\n"), "{}", renderer);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_supports_renderer() -> Result<()> {
+        let preprocessor = TabsPreprocessor::builder()
+            .renderers(["custom-html"])
+            .build()?;
+
+        for renderer in ["html", "custom-html", "epub", "markdown"] {
+            assert!(preprocessor.supports_renderer(renderer), "{}", renderer);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_process_content_escaped_marker() -> Result<()> {
+        let content = "\
+        Tabs start with \\{{#tabs }} and end with \\{{#endtabs }}.\n\
+        \n\
+        \\{{#tabs }}\n\
+        \n\
+        {{#tabs }}\n\
+        {{#tab name=\"Linux\" }}\n\
+        Use `\\{{#tab }}`, not \\{{#tab }}.\n\
+        {{#endtab }}\n\
+        {{#tab name=\"macOS\" }}\n\
+        macOS.\n\
+        {{#endtab }}\n\
+        {{#endtabs }}\n\
+        \n\
+        ```\n\
+        \\{{#tabs }}\n\
+        ```\n";
+
+        let config = PreprocessorConfig::default();
+        let mut ctx = ChapterContext::new(&config, "test.md".to_string(), content);
+        let actual = process_content(&mut ctx, content, &Offsets::default())?;
+
+        assert_eq!(1, ctx.group);
+        assert!(actual.starts_with(
+            "\
+            Tabs start with {{#tabs }} and end with {{#endtabs }}.\n\
+            \n\
+            {{#tabs }}\n\
+            \n\
+            <div class=\"mdbook-tabs-container\">"
+        ));
+        assert!(actual.contains("\nUse `\\{{#tab }}`, not {{#tab }}.\n"));
+        assert!(actual.ends_with("```\n\\{{#tabs }}\n```\n"));
+        assert_eq!(None, inline_marker(content, &Markers::new(&config)));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_process_content_crlf() -> Result<()> {
+        let content = "\
+        Before.\r\n\
+        \r\n\
+        {{#tabs }}\r\n\
+        {{#tab name=\"Linux\" }}\r\n\
+        Install with `apt`.\r\n\
+        {{#endtab }}\r\n\
+        {{#tab name=\"macOS\" }}\r\n\
+        \r\n\
+        ```sh\r\n\
+        brew install example\r\n\
+        ```\r\n\
+        \r\n\
+        {{#endtab }}\r\n\
+        {{#endtabs }}\r\n\
+        \r\n\
+        After.\r\n";
+
+        let config = PreprocessorConfig::default();
+        let actual = process_content(
+            &mut ChapterContext::new(&config, "test.md".to_string(), content),
+            content,
+            &Offsets::default(),
+        )?;
+
+        let start = actual
+            .find("<div class=\"mdbook-tabs-container\">")
+            .unwrap();
+        let end = actual.rfind("</div>").unwrap();
+        assert!(actual.starts_with("Before.\r\n\r\n"));
+        assert!(actual.ends_with("</div>\r\n\r\nAfter.\r\n"));
+        assert!(!actual[start..end].contains('\r'));
+        assert!(actual[start..end].contains("\n\nInstall with `apt`.\n\n\n</div>"));
+        assert!(actual[start..end].contains("```sh\nbrew install example\n```"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_run_after_links() -> Result<()> {
+        let root = env::temp_dir().join(format!("mdbook-tabs-test-{}", process::id()));
+        fs::create_dir_all(root.join("src"))?;
+        fs::write(
+            root.join("src/linux.md"),
+            "\
+            Install with `apt`.\n\
+            \n\
+            {{#tabs }}\n\
+            {{#tab name=\"Debian\" }}\n\
+            apt install example\n\
+            {{#endtab }}\n\
+            {{#tab name=\"Ubuntu\" }}\n\
+            apt-get install example\n\
+            {{#endtab }}\n\
+            {{#endtabs }}\n",
+        )?;
+
+        let ctx: PreprocessorContext = serde_json::from_value(serde_json::json!({
+            "root": root,
+            "config": {},
+            "renderer": "html",
+            "mdbook_version": mdbook::MDBOOK_VERSION,
+        }))?;
+
+        let mut book = Book::new();
+        book.push_item(Chapter::new(
+            "Chapter",
+            "\
+            {{#tabs }}\n\
+            {{#tab name=\"Linux\" }}\n\
+            {{#include linux.md}}\n\
+            {{#endtab }}\n\
+            {{#tab name=\"macOS\" }}\n\
+            brew install example\n\
+            {{#endtab }}\n\
+            {{#endtabs }}"
+                .to_string(),
+            "chapter.md",
+            vec![],
+        ));
+
+        let book = LinkPreprocessor::new().run(&ctx, book)?;
+        let book = TabsPreprocessor::new().run(&ctx, book)?;
+        fs::remove_dir_all(&root)?;
+
+        let BookItem::Chapter(chapter) = &book.sections[0] else {
+            bail!("First item should be a chapter.");
+        };
+        assert!(!chapter.content.contains("{{#"));
+        assert!(chapter.content.contains("Install with `apt`."));
+        assert!(chapter.content.contains("id=\"mdbook-tab-0-linux\""));
+        assert!(chapter.content.contains("id=\"mdbook-tab-1-debian\""));
+        assert!(chapter.content.contains("id=\"mdbook-tab-0-macos\""));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_run_with_report() -> Result<()> {
+        let root = env::temp_dir().join(format!("mdbook-tabs-report-test-{}", process::id()));
+        fs::create_dir_all(&root)?;
+        let report_path = root.join("report.json");
+
+        let ctx: PreprocessorContext = serde_json::from_value(serde_json::json!({
+            "root": root,
+            "config": {},
+            "renderer": "html",
+            "mdbook_version": mdbook::MDBOOK_VERSION,
+        }))?;
+
+        let mut book = Book::new();
+        book.push_item(Chapter::new(
+            "Install",
+            "\
+            # Install\n\
+            \n\
+            {{#tabs }}\n\
+            {{#tab name=\"Linux\" }}\n\
+            {{#tabs }}\n\
+            {{#tab name=\"Debian\" }}\n\
+            apt install example\n\
+            {{#endtab }}\n\
+            {{#tab name=\"Fedora\" }}\n\
+            dnf install example\n\
+            {{#endtab }}\n\
+            {{#endtabs }}\n\
+            {{#endtab }}\n\
+            {{#tab name=\"macOS\" }}\n\
+            brew install example\n\
+            {{#endtab }}\n\
+            {{#endtabs }}\n"
+                .to_string(),
+            "install.md",
+            vec![],
+        ));
+        book.push_item(Chapter::new(
+            "Usage",
+            "No tabs.".to_string(),
+            "usage.md",
+            vec![],
+        ));
+
+        let (expected, expected_stats) =
+            TabsPreprocessor::new().run_with_stats(&ctx, book.clone())?;
+        let (actual, stats) =
+            TabsPreprocessor::new().run_with_report(&ctx, book, Some(&report_path))?;
+        assert_eq!(
+            serde_json::to_string(&expected)?,
+            serde_json::to_string(&actual)?
+        );
+        assert_eq!(expected_stats, stats);
+        assert_eq!(
+            TabsStats {
+                chapters: vec![
+                    ChapterStats {
+                        chapter: "install.md".to_string(),
+                        groups: 2,
+                    },
+                    ChapterStats {
+                        chapter: "usage.md".to_string(),
+                        groups: 0,
+                    },
+                ],
+                total: 2,
+            },
+            stats
+        );
+
+        let report: Vec<TabsReport> = serde_json::from_slice(&fs::read(&report_path)?)?;
+        fs::remove_dir_all(&root)?;
+
+        assert_eq!(
+            vec![
+                TabsReport {
+                    chapter: "install.md".to_string(),
+                    span: 11..267,
+                    labels: vec!["Linux".to_string(), "macOS".to_string()],
+                },
+                TabsReport {
+                    chapter: "install.md".to_string(),
+                    span: 45..183,
+                    labels: vec!["Debian".to_string(), "Fedora".to_string()],
+                },
+            ],
+            report
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_process_book() -> Result<()> {
+        let mut book = Book::new();
+        book.push_item(Chapter::new(
+            "Install",
+            "\
+            {{#tabs }}\n\
+            {{#tab name=\"Linux\" }}\n\
+            apt install example\n\
+            {{#endtab }}\n\
+            {{#tab name=\"macOS\" }}\n\
+            brew install example\n\
+            {{#endtab }}\n\
+            {{#endtabs }}\n"
+                .to_string(),
+            "install.md",
+            vec![],
+        ));
+
+        let ctx: PreprocessorContext = serde_json::from_value(serde_json::json!({
+            "root": env::temp_dir(),
+            "config": {"preprocessor": {"tabs": {"cache": false}}},
+            "renderer": "html",
+            "mdbook_version": mdbook::MDBOOK_VERSION,
+        }))?;
+        let expected = TabsPreprocessor::new().run(&ctx, book.clone())?;
+        let actual = TabsPreprocessor::new().process_book(book.clone())?;
+        assert_eq!(
+            serde_json::to_string(&expected)?,
+            serde_json::to_string(&actual)?
+        );
+
+        let actual = TabsPreprocessor::builder()
+            .wrapper_tag("section")
+            .build()?
+            .process_book(book)?;
+        let BookItem::Chapter(chapter) = &actual.sections[0] else {
+            bail!("First item should be a chapter.");
+        };
+        assert!(chapter
+            .content
+            .starts_with("<section class=\"mdbook-tabs-container\""));
+        assert!(chapter.content.contains("id=\"mdbook-tab-0-macos\""));
+        assert!(!chapter.content.contains("{{#"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_process_book_data_file() -> Result<()> {
+        let root = env::temp_dir().join(format!("mdbook-tabs-data-test-{}", process::id()));
+        fs::create_dir_all(root.join("src/install/variants"))?;
+        fs::write(
+            root.join("src/install/variants.yaml"),
+            "Linux: variants/linux.md\n\"macOS\": variants/macos.md\n",
+        )?;
+        fs::write(
+            root.join("src/install/variants/linux.md"),
+            "apt install example\n",
+        )?;
+        fs::write(
+            root.join("src/install/variants/macos.md"),
+            "brew install example\n",
+        )?;
+
+        let ctx: PreprocessorContext = serde_json::from_value(serde_json::json!({
+            "root": root,
+            "config": {},
+            "renderer": "html",
+            "mdbook_version": mdbook::MDBOOK_VERSION,
+        }))?;
+
+        let chapter = |content: &str| {
+            let mut book = Book::new();
+            book.push_item(Chapter::new(
+                "Install",
+                content.to_string(),
+                "install/index.md",
+                vec![],
+            ));
+            book
+        };
+
+        let book = TabsPreprocessor::new().run(
+            &ctx,
+            chapter("{{#tabs from=\"variants.yaml\" }}\n{{#endtabs }}\n"),
+        )?;
+        let error = TabsPreprocessor::new()
+            .run(
+                &ctx,
+                chapter("{{#tabs from=\"missing.yaml\" }}\n{{#endtabs }}\n"),
+            )
+            .map(|_| ())
+            .unwrap_err();
+        fs::remove_dir_all(&root)?;
+
+        let BookItem::Chapter(chapter) = &book.sections[0] else {
+            bail!("First item should be a chapter.");
+        };
+        assert!(chapter.content.contains("id=\"mdbook-tab-0-linux\""));
+        assert!(chapter.content.contains("id=\"mdbook-tab-0-macos\""));
+        assert!(chapter.content.contains("apt install example"));
+        assert!(chapter.content.contains("brew install example"));
+        assert!(!chapter.content.contains("{{#"));
+
+        let message = format!("{:#}", error);
+        assert!(message.starts_with("Failed to read tabs data file `"));
+        assert!(message.contains(&format!(
+            "{}` of tabs at install/index.md:1:1.",
+            root.join("src/install/missing.yaml").display()
+        )));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_run_without_tabs() -> Result<()> {
+        let content = "\
+        # Chapter\r\n\
+        \r\n\
+        Text with trailing spaces  \n\
+        and \\*escapes\\*, &amp; entities, <span>HTML</span> and `{{#tabs }}` in code.\n\
+        \n\
+        | Column | Other |\n\
+        |--------|-------|\n\
+        | 1      | 2     |\n\
+        \n\
+        ```markdown\n\
+        {{#tabs }}\n\
+        :::tabs\n\
+        ```\n\
+        \n\
+        #### Heading {#custom-id}\n\
+        \n\
+        **Bold**\n\
+        \n\
+        \t* List item\n\
+        \n\
+        \n\
+        No newline at the end.";
+
+        for (renderer, config) in [
+            ("html", serde_json::json!({})),
+            ("markdown", serde_json::json!({})),
+            (
+                "html",
+                serde_json::json!({"preprocessor": {"tabs": {"syntax": "colon"}}}),
+            ),
+            (
+                "html",
+                serde_json::json!({"preprocessor": {"tabs": {"heading_level": 4}}}),
+            ),
+            (
+                "html",
+                serde_json::json!({"preprocessor": {"tabs": {"delimiter": "bold"}}}),
+            ),
+        ] {
+            let ctx: PreprocessorContext = serde_json::from_value(serde_json::json!({
+                "root": env::temp_dir(),
+                "config": config,
+                "renderer": renderer,
+                "mdbook_version": mdbook::MDBOOK_VERSION,
+            }))?;
+
+            let mut book = Book::new();
+            book.push_item(Chapter::new(
+                "Chapter",
+                content.to_string(),
+                "chapter.md",
+                vec![],
+            ));
+
+            let book = TabsPreprocessor::new().run(&ctx, book)?;
+            let BookItem::Chapter(chapter) = &book.sections[0] else {
+                bail!("First item should be a chapter.");
+            };
+            assert_eq!(content, chapter.content, "{} {}", renderer, config);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_unresolved_link() {
+        assert_eq!(Some(6), unresolved_link("Text: {{#include file.md}}"));
+        assert_eq!(None, unresolved_link("```md\n{{#include file.md}}\n```"));
+        assert_eq!(None, unresolved_link("Inline `{{#include file.md}}`."));
+    }
+
+    #[test]
+    fn test_process_content_max_blocks() {
+        let content = "\
+        {{#tabs }}\n\
+        {{#tab name=\"Linux\" }}\n\
+        Linux.\n\
+        {{#endtab }}\n\
+        {{#tab name=\"macOS\" }}\n\
+        macOS.\n\
+        {{#endtab }}\n\
+        {{#endtabs }}";
+
+        let config = PreprocessorConfig {
+            max_blocks: Some(1),
+            ..Default::default()
+        };
+        let actual = process_content(
+            &mut ChapterContext::new(&config, "test.md".to_string(), content),
+            content,
+            &Offsets::default(),
+        );
+
+        let error = actual.unwrap_err();
+        assert_eq!("Failed to parse tabs at test.md:5:1.", error.to_string());
+        assert_eq!(
+            Some(&BlockParseError::TooManyBlocks {
+                span: 54..76,
+                max: 1
+            }),
+            error.downcast_ref::<BlockParseError>()
+        );
+    }
+
+    #[test]
+    fn test_strip_markers() {
+        let actual = TabsPreprocessor::strip_markers(
+            "\
+            # Install\n\
+            \n\
+            {{#tabs }}\n\
+            {{#tab name=\"Linux\" }}\n\
+            {{#tabs }}\n\
+            {{#tab name=\"apt\" }}\n\
+            apt install example\n\
+            {{#endtab }}\n\
+            {{#tab name=\"dnf\" }}\n\
+            dnf install example\n\
+            {{#endtab }}\n\
+            {{#endtabs }}\n\
+            {{#endtab }}\n\
+            {{#tab name=\"macOS\" }}\n\
+            brew install example\n\
+            {{#endtab }}\n\
+            {{#endtabs }}\n\
+            \n\
+            {{#tabs }}\n\
+            {{#tab name=\"Cargo\" }}\n\
+            cargo add example\n\
+            {{#endtab }}\n\
+            {{#tab name=\"npm\" }}\n\
+            npm install example\n\
+            {{#endtab }}\n\
+            {{#endtabs }}\n",
+        );
+
+        assert!(!actual.contains("{{#"));
+        assert!(!actual.contains("<div"));
+        assert!(actual.starts_with("# Install\n\n### Linux\n\n### apt\n\napt install example\n"));
+        for heading in ["### dnf\n", "### macOS\n", "### Cargo\n", "### npm\n"] {
+            assert_eq!(1, actual.matches(heading).count());
+        }
+        assert!(actual.ends_with("### npm\n\nnpm install example\n\n"));
+    }
+
+    #[test]
+    fn test_process_content_html() -> Result<()> {
+        let content = "\
+        {{#tabs }}\n\
+        {{#tab name=\"Linux\" }}\n\
+        <div class=\"warning\">\n\
+        Run as <b>root</b> & reboot.\n\
+        </div>\n\
+        {{#endtab }}\n\
+        {{#tab name=\"macOS\" }}\n\
+        Press <kbd>Cmd</kbd> + <kbd>Space</kbd> & type `Terminal`.\n\
+        {{#endtab }}\n\
+        {{#endtabs }}";
+
+        let config = PreprocessorConfig::default();
+        let actual = process_content(
+            &mut ChapterContext::new(&config, "test.md".to_string(), content),
+            content,
+            &Offsets::default(),
+        )?;
+
+        assert!(!actual.contains("{{#"));
+        assert!(actual.contains(
+            "aria-labelledby=\"mdbook-tab-0-linux\">\n\n<div class=\"warning\">\nRun as <b>root</b> & reboot.\n</div>\n\n\n</div>"
+        ));
+        assert!(actual.contains("\nPress <kbd>Cmd</kbd> + <kbd>Space</kbd> & type `Terminal`.\n"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_process_items_inline_assets_chapters() -> Result<()> {
+        let content = "\
+        {{#tabs }}\n\
+        {{#tab name=\"Linux\" }}\n\
+        Linux.\n\
+        {{#endtab }}\n\
+        {{#tab name=\"macOS\" }}\n\
+        macOS.\n\
+        {{#endtab }}\n\
+        {{#endtabs }}\n";
+
+        let config = PreprocessorConfig {
+            inline_assets: true,
+            ..Default::default()
+        };
+        let mut items = vec![
+            BookItem::Chapter(Chapter::new(
+                "First",
+                content.to_string(),
+                "first.md",
+                vec![],
+            )),
+            BookItem::Chapter(Chapter::new(
+                "Second",
+                content.to_string(),
+                "second.md",
+                vec![],
+            )),
+        ];
+        process_items(
+            &mut items,
+            &config,
+            None,
+            &mut vec![],
+            &mut TabsStats::default(),
+        )?;
+
+        // The chapters are joined in `print.html`, so the second script is skipped by the guard and
+        // its declarations are scoped to the function.
+        let script = format!("<script>\n{}</script>\n", inline_js(&config));
+        for item in &items {
+            let BookItem::Chapter(chapter) = item else {
+                bail!("Item should be a chapter.");
+            };
+            assert_eq!(1, chapter.content.matches(&script).count());
+        }
+        assert!(script.starts_with(
+            "<script>\n(() => {\nif (window.__mdbookTabs) {\n    return;\n}\nwindow.__mdbookTabs = true;\n\n"
+        ));
+        assert!(script.ends_with("});\n})();\n</script>\n"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_process_items_inline_assets_labels() -> Result<()> {
+        let content = "\
+        {{#tabs global=\"it's & more\" }}\n\
+        \n\
+        #### He said \"hi\" \\\n\
+        \n\
+        Quoted.\n\
+        \n\
+        #### </script><script>alert(1)</script>\n\
+        \n\
+        Script.\n\
+        \n\
+        {{#endtabs }}";
+
+        let config = PreprocessorConfig {
+            inline_assets: true,
+            remember: true,
+            heading_level: Some(4),
+            ..Default::default()
+        };
+        let mut items = vec![BookItem::Chapter(Chapter::new(
+            "Chapter",
+            content.to_string(),
+            "chapter.md",
+            vec![],
+        ))];
+        process_items(
+            &mut items,
+            &config,
+            None,
+            &mut vec![],
+            &mut TabsStats::default(),
+        )?;
+
+        let BookItem::Chapter(chapter) = &items[0] else {
+            panic!("Expected a chapter.");
+        };
+
+        let script = format!("<script>\n{}</script>\n", inline_js(&config));
+        let script_start = chapter.content.find("<script>").unwrap();
+        assert_eq!(
+            Some(script_start + script.len()),
+            chapter
+                .content
+                .find("</script>")
+                .map(|index| index + "</script>\n".len())
+        );
+        assert_eq!(
+            script,
+            chapter.content[script_start..script_start + script.len()]
+        );
+
+        assert!(chapter
+            .content
+            .contains("data-tabglobal=\"it&#39;s &amp; more\""));
+        assert!(chapter.content.contains(
+            "data-tabremember=\"[&quot;He said \\&quot;hi\\&quot; \\\\&quot;,&quot;&lt;/script&gt;&lt;script&gt;alert(1)&lt;/script&gt;&quot;]\""
+        ));
+        assert!(chapter
+            .content
+            .contains("data-tabname=\"He said &quot;hi&quot; \\\""));
+        assert!(chapter
+            .content
+            .contains("data-tabname=\"&lt;/script&gt;&lt;script&gt;alert(1)&lt;/script&gt;\""));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_process_items_disabled() -> Result<()> {
+        let content = "\
+        {{#tabs }}\n\
+        {{#tab name=\"Linux\" }}\n\
+        Linux.\n\
+        {{#endtab }}\n\
+        {{#endtabs }}";
+
+        let config = PreprocessorConfig {
+            disabled_chapters: vec!["guide\\listed.md".to_string()],
+            ..Default::default()
+        };
+        let mut items = [
+            (
+                "directive.md",
+                format!("\n{{{{#tabs-disable}}}}\r\n{}", content),
+            ),
+            ("guide/listed.md", content.to_string()),
+            ("enabled.md", content.to_string()),
+            (
+                "inline.md",
+                format!("{{{{#tabs-disable}}}} text\n{}", content),
+            ),
+        ]
+        .into_iter()
+        .map(|(path, content)| BookItem::Chapter(Chapter::new("Chapter", content, path, vec![])))
+        .collect();
+        process_items(
+            &mut items,
+            &config,
+            None,
+            &mut vec![],
+            &mut TabsStats::default(),
+        )?;
+
+        let contents = items
+            .iter()
+            .map(|item| match item {
+                BookItem::Chapter(chapter) => chapter.content.as_str(),
+                _ => panic!("Expected a chapter."),
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(content, contents[0]);
+        assert_eq!(content, contents[1]);
+        assert!(contents[2].contains("mdbook-tabs-container"));
+        assert!(contents[3].contains("mdbook-tabs-container"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_process_items_cache() -> Result<()> {
+        let directory =
+            env::temp_dir().join(format!("mdbook-tabs-items-cache-test-{}", process::id()));
+        let content = "\
+        {{#tabs }}\n\
+        {{#tab name=\"Linux\" }}\n\
+        Linux.\n\
+        {{#endtab }}\n\
+        {{#endtabs }}";
+
+        let config = PreprocessorConfig::default();
+        let cache = Cache::new(directory.clone(), &config)?;
+        let chapter = || {
+            vec![BookItem::Chapter(Chapter::new(
+                "Chapter",
+                content.to_string(),
+                "chapter.md",
+                vec![],
+            ))]
+        };
+        let chapter_content = |items: &[BookItem]| match &items[0] {
+            BookItem::Chapter(chapter) => chapter.content.clone(),
+            _ => panic!("Expected a chapter."),
+        };
+
+        let mut items = chapter();
+        process_items(
+            &mut items,
+            &config,
+            Some(&cache),
+            &mut vec![],
+            &mut TabsStats::default(),
+        )?;
+        let processed = chapter_content(&items);
+        assert!(processed.contains("mdbook-tabs-container"));
+        assert_eq!(Some((processed, 1)), cache.get("chapter.md", content));
+
+        cache.set("chapter.md", content, "Cached.", 1);
+        let mut items = chapter();
+        process_items(
+            &mut items,
+            &config,
+            Some(&cache),
+            &mut vec![],
+            &mut TabsStats::default(),
+        )?;
+        assert_eq!("Cached.", chapter_content(&items));
+
+        fs::remove_dir_all(directory)?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_validate() {
+        let mut book = Book::new();
+        book.push_item(Chapter::new(
+            "Chapter",
+            "\
+            {{#tabs }}\n\
+            {{#tab name=\"Linux\" }}\n\
+            {{#tabs }}\n\
+            {{#endtabs }}\n\
+            {{#endtab }}\n\
+            {{#tab name=\"Linux\" }}\n\
+            Linux.\n\
+            {{#endtab }}\n\
+            {{#endtabs }}\n\
+            \n\
+            {{#tabs }}\n\
+            {{#tab name=\"macOS\" }}\n\
+            macOS.\n"
+                .to_string(),
+            "chapter.md",
+            vec![],
+        ));
+
+        let actual = TabsPreprocessor::new().validate(&book, &PreprocessorConfig::default());
+
+        assert_eq!(
+            vec![
+                "chapter.md:1:1 (0..128): Tabs contain multiple tabs named `Linux`.",
+                "chapter.md:3:1 (34..58): Tabs do not contain any tab.",
+                "chapter.md:11:1 (130..140): Tabs are not closed.",
+            ],
+            actual
+        );
+    }
+
+    #[test]
+    fn test_validate_book_data_file() -> Result<()> {
+        let root = env::temp_dir().join(format!("mdbook-tabs-validate-test-{}", process::id()));
+        fs::create_dir_all(root.join("src/install/variants"))?;
+        fs::write(root.join("book.toml"), "[book]\ntitle = \"Example\"\n")?;
+        fs::write(
+            root.join("src/SUMMARY.md"),
+            "# Summary\n\n- [Install](install/index.md)\n",
+        )?;
+        fs::write(
+            root.join("src/install/index.md"),
+            "{{#tabs from=\"variants.yaml\" }}\n{{#endtabs }}\n",
+        )?;
+        fs::write(
+            root.join("src/install/variants.yaml"),
+            "Linux: variants/linux.md\n",
+        )?;
+        fs::write(
+            root.join("src/install/variants/linux.md"),
+            "apt install example\n",
+        )?;
+
+        let book = MDBook::load(&root)?;
+        let actual = TabsPreprocessor::new().validate_book(&book);
+        let without_book =
+            TabsPreprocessor::new().validate(&book.book, &PreprocessorConfig::default());
+        let processed = TabsPreprocessor::new().process_book(book.book.clone());
+        fs::remove_dir_all(&root)?;
+
+        assert!(actual?.is_empty());
+
+        // Without a book, the data file is not read relative to the current directory.
+        let message = "Tabs at install/index.md:1:1 read from `variants.yaml`, which requires the source directory of the book.";
+        assert_eq!(
+            vec![format!("install/index.md:1:1 (0..45): {}", message)],
+            without_book
+        );
+        assert_eq!(message, processed.map(|_| ()).unwrap_err().to_string());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_validate_book_include() -> Result<()> {
+        let root = env::temp_dir().join(format!("mdbook-tabs-include-test-{}", process::id()));
+        fs::create_dir_all(root.join("src"))?;
+        fs::write(root.join("book.toml"), "[book]\ntitle = \"Example\"\n")?;
+        fs::write(
+            root.join("src/SUMMARY.md"),
+            "# Summary\n\n- [Install](install.md)\n",
+        )?;
+        fs::write(
+            root.join("src/install.md"),
+            "{{#tabs }}\n{{#tab name=\"Linux\" }}\napt.\n{{#endtab }}\n{{#include macos.md}}\n",
+        )?;
+        fs::write(
+            root.join("src/macos.md"),
+            "{{#tab name=\"macOS\" }}\nbrew.\n{{#endtab }}\n{{#endtabs }}\n",
+        )?;
+
+        let book = MDBook::load(&root)?;
+        let actual = TabsPreprocessor::new().validate_book(&book);
+        let without_links =
+            TabsPreprocessor::new().validate(&book.book, &PreprocessorConfig::default());
+        fs::remove_dir_all(&root)?;
+
+        assert!(actual?.is_empty());
+        assert_eq!(1, without_links.len());
+        assert!(without_links[0].ends_with(": Tabs are not closed."));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_duplicate_names_scope() -> Result<()> {
+        let content = "\
+        {{#tabs }}\n\
+        {{#tab name=\"Linux\" }}\n\
+        apt.\n\
+        {{#endtab }}\n\
+        {{#tab name=\"macOS\" }}\n\
+        brew.\n\
+        {{#endtab }}\n\
+        {{#tab name=\"Linux\" }}\n\
+        dnf.\n\
+        {{#endtab }}\n\
+        {{#endtabs }}\n\
+        \n\
+        {{#tabs }}\n\
+        {{#tab name=\"Linux\" }}\n\
+        Linux.\n\
+        {{#endtab }}\n\
+        {{#tab name=\"macOS\" }}\n\
+        macOS.\n\
+        {{#endtab }}\n\
+        {{#endtabs }}\n";
+
+        let config = PreprocessorConfig::default();
+        let configs = parse_tabs(content, &config, &Markers::new(&config))?;
+        assert_eq!(vec!["Linux"], duplicate_names(&configs[0].1));
+        assert!(duplicate_names(&configs[1].1).is_empty());
+
+        let mut book = Book::new();
+        book.push_item(Chapter::new(
+            "Chapter",
+            content.to_string(),
+            "chapter.md",
+            vec![],
+        ));
+        assert_eq!(
+            vec!["chapter.md:1:1 (0..148): Tabs contain multiple tabs named `Linux`."],
+            TabsPreprocessor::new().validate(&book, &config)
+        );
+
+        let mut ctx = ChapterContext::new(&config, "test.md".to_string(), content);
+        let actual = process_content(&mut ctx, content, &Offsets::default())?;
+        for id in [
+            "mdbook-tab-0-linux",
+            "mdbook-tab-0-linux-2",
+            "mdbook-tab-1-linux",
+            "mdbook-tab-content-0-linux",
+            "mdbook-tab-content-0-linux-2",
+            "mdbook-tab-content-1-linux",
+        ] {
+            assert_eq!(
+                1,
+                actual.matches(&format!("id=\"{}\"", id)).count(),
+                "{}",
+                id
+            );
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_render_block() -> Result<()> {
+        let preprocessor = TabsPreprocessor::new();
+
+        let actual = preprocessor.render_block(
+            "\
+            {{#tabs }}\n\
+            {{#tab name=\"Linux\" }}\n\
+            Some content.\n\
+            {{#endtab }}\n\
+            {{#endtabs }}",
+        )?;
+        assert!(actual.starts_with("<div class=\"mdbook-tabs-container\">"));
+        assert!(actual.contains("Some content."));
+
+        assert!(preprocessor.render_block("No tabs.").is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_generated_assets() -> Result<()> {
+        let preprocessor = TabsPreprocessor::new();
+        assert!(preprocessor.generated_css().contains(".mdbook-tabs {"));
+        assert!(preprocessor
+            .generated_js()
+            .contains("const ACTIVE_CLASS = 'active';"));
+
+        let preprocessor = TabsPreprocessor::builder()
+            .class_prefix("docs-")
+            .active_class("is-active")
+            .build()?;
+        let css = preprocessor.generated_css();
+        assert!(css.contains(".docs-tabs {"));
+        assert!(css.contains(".docs-tab.is-active {"));
+        assert!(!css.contains(".mdbook-"));
+        assert!(preprocessor
+            .generated_js()
+            .contains("const ACTIVE_CLASS = 'is-active';"));
+
+        Ok(())
+    }
+}
@@ -0,0 +1,18 @@
+use std::{fs, ops::Range, path::Path};
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+
+pub const REPORT_VARIABLE: &str = "MDBOOK_TABS_REPORT";
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct TabsReport {
+    pub chapter: String,
+    pub span: Range<usize>,
+    pub labels: Vec<String>,
+}
+
+pub fn write_report(path: &Path, report: &[TabsReport]) -> Result<()> {
+    fs::write(path, serde_json::to_string_pretty(report)?)
+        .with_context(|| format!("Failed to write tabs report to `{}`.", path.display()))
+}
@@ -0,0 +1,19 @@
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ChapterStats {
+    pub chapter: String,
+    pub groups: usize,
+}
+
+// Number of tabs groups processed by `TabsPreprocessor::run_with_stats`, including nested tabs.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct TabsStats {
+    pub chapters: Vec<ChapterStats>,
+    pub total: usize,
+}
+
+impl TabsStats {
+    pub(crate) fn push(&mut self, chapter: String, groups: usize) {
+        self.chapters.push(ChapterStats { chapter, groups });
+        self.total += groups;
+    }
+}
@@ -1,42 +1,1042 @@
-use crate::config::TabsConfig;
+use std::ops::Range;
+
+use log::warn;
+use mdbook_plugin_utils::html::escape;
+use pulldown_cmark::{html::push_html, Event, Parser, Tag, TagEnd};
+
+use crate::{
+    config::{Orientation, PreprocessorConfig, RenderMode, Responsive, TabConfig, TabsConfig},
+    parser::options,
+};
+
+pub fn active_index(config: &TabsConfig) -> usize {
+    match &config.default {
+        Some(default) => match config.tabs.iter().position(|(tab, _)| &tab.name == default) {
+            Some(index) => index,
+            None => {
+                warn!(
+                    "Default tab `{}` does not exist, falling back to the first tab.",
+                    default
+                );
+                0
+            }
+        },
+        None => 0,
+    }
+}
+
+pub fn order_tabs(config: &mut TabsConfig) -> Vec<String> {
+    let Some(order) = &config.order else {
+        return vec![];
+    };
+
+    let order = order
+        .split(',')
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+        .collect::<Vec<_>>();
+
+    config.tabs.sort_by_key(|(tab, _)| {
+        order
+            .iter()
+            .position(|name| *name == tab.name)
+            .unwrap_or(order.len())
+    });
+
+    order
+        .into_iter()
+        .filter(|name| !config.tabs.iter().any(|(tab, _)| tab.name == *name))
+        .map(str::to_string)
+        .collect()
+}
+
+fn is_paragraph(event: &Event) -> bool {
+    matches!(
+        event,
+        Event::Start(Tag::Paragraph) | Event::End(TagEnd::Paragraph)
+    )
+}
+
+pub fn label(name: &str) -> String {
+    let mut html = String::new();
+    push_html(
+        &mut html,
+        Parser::new_ext(name, options()).filter(|event| !is_paragraph(event)),
+    );
+
+    html.trim().to_string()
+}
+
+pub fn plain_text(name: &str) -> String {
+    Parser::new_ext(name, options())
+        .filter_map(|event| match event {
+            Event::Text(text) | Event::Code(text) => Some(text.to_string()),
+            Event::SoftBreak | Event::HardBreak => Some(" ".to_string()),
+            _ => None,
+        })
+        .collect()
+}
+
+pub fn slug(value: &str) -> String {
+    let mut slug = String::with_capacity(value.len());
+
+    for c in value.chars().flat_map(char::to_lowercase) {
+        if c.is_alphanumeric() {
+            slug.push(c);
+        } else if !slug.is_empty() && !slug.ends_with('-') {
+            slug.push('-');
+        }
+    }
+
+    slug.trim_end_matches('-').to_string()
+}
+
+fn tab_slug(tab: &TabConfig, index: usize) -> String {
+    let slug = slug(&plain_text(&tab.name));
+
+    match slug.is_empty() {
+        true => index.to_string(),
+        false => slug,
+    }
+}
+
+fn tab_slugs(config: &TabsConfig) -> Vec<String> {
+    let mut slugs: Vec<String> = Vec::with_capacity(config.tabs.len());
+
+    for (index, (tab, _)) in config.tabs.iter().enumerate() {
+        let mut slug = tab_slug(tab, index);
+
+        // The index is appended to duplicates, and increased while it matches the slug of another
+        // tab, e.g. a tab named `a-2`.
+        let mut suffix = index;
+        let base = slug.clone();
+        while slugs.contains(&slug) {
+            slug = format!("{}-{}", base, suffix);
+            suffix += 1;
+        }
+
+        slugs.push(slug);
+    }
+
+    slugs
+}
+
+fn tab_id(group: &str, slug: &str) -> String {
+    format!("mdbook-tab-{}-{}", group, slug)
+}
+
+fn tab_content_id(group: &str, slug: &str) -> String {
+    format!("mdbook-tab-content-{}-{}", group, slug)
+}
+
+fn order(preprocessor_config: &PreprocessorConfig, order: usize) -> String {
+    match preprocessor_config.responsive {
+        Some(Responsive::Accordion) => format!(" style=\"order: {}\"", order),
+        None => "".to_string(),
+    }
+}
+
+// Indentation stays below four spaces at the start of HTML blocks, so it is never an indented code
+// block. Tab contents are never indented.
+fn indent(preprocessor_config: &PreprocessorConfig, level: usize) -> String {
+    match preprocessor_config.pretty {
+        true => "  ".repeat(level),
+        false => "".to_string(),
+    }
+}
+
+fn icon(tab: &TabConfig, preprocessor_config: &PreprocessorConfig) -> String {
+    match &tab.icon {
+        Some(icon) => format!(
+            "<span class=\"{} {}\" aria-hidden=\"true\"></span>",
+            preprocessor_config.class("tab-icon"),
+            escape(&preprocessor_config.class(&format!("tab-icon-{}", icon)))
+        ),
+        None => "".to_string(),
+    }
+}
+
+fn tabs_title_id(group: &str) -> String {
+    format!("mdbook-tabs-title-{}", group)
+}
+
+// The title is the accessible label of the tablist, so it is referenced by its ID.
+fn title(config: &TabsConfig, preprocessor_config: &PreprocessorConfig, group: &str) -> String {
+    match &config.title {
+        Some(title) => format!(
+            "{}<div class=\"{}\" id=\"{}\">{}</div>\n",
+            indent(preprocessor_config, 1),
+            preprocessor_config.class("tabs-title"),
+            tabs_title_id(group),
+            escape(title)
+        ),
+        None => "".to_string(),
+    }
+}
+
+fn tab(
+    tab: &TabConfig,
+    preprocessor_config: &PreprocessorConfig,
+    group: &str,
+    index: usize,
+    slug: &str,
+    active: bool,
+    level: usize,
+) -> String {
+    format!(
+        "{}<button class=\"{}{}\" data-tabname=\"{}\" data-tab-label=\"{}\" id=\"{}\" role=\"tab\" aria-selected=\"{}\" aria-controls=\"{}\" tabindex=\"{}\"{}>{}{}</button>",
+        indent(preprocessor_config, level),
+        preprocessor_config.class("tab"),
+        match active {
+            true => format!(" {}", preprocessor_config.active_class),
+            false => "".to_string(),
+        },
+        escape(&tab.name),
+        escape(&tab.name),
+        tab_id(group, slug),
+        active,
+        tab_content_id(group, slug),
+        match active {
+            true => "0",
+            false => "-1",
+        },
+        order(preprocessor_config, 2 * index),
+        icon(tab, preprocessor_config),
+        label(&tab.name)
+    )
+}
+
+fn tabs_more_id(group: &str) -> String {
+    format!("mdbook-tabs-more-{}", group)
+}
+
+// Tabs after `max_visible_tabs` are in a menu opened by a "More" button, which is part of the
+// tablist so the arrow keys move between all tabs.
+fn tab_buttons(
+    config: &TabsConfig,
+    preprocessor_config: &PreprocessorConfig,
+    group: &str,
+    slugs: &[String],
+    active_index: usize,
+) -> String {
+    let visible = preprocessor_config
+        .max_visible_tabs
+        .unwrap_or(usize::MAX)
+        .min(config.tabs.len());
+    let buttons = |range: Range<usize>, level: usize| {
+        range
+            .map(|index| {
+                tab(
+                    &config.tabs[index].0,
+                    preprocessor_config,
+                    group,
+                    index,
+                    &slugs[index],
+                    index == active_index,
+                    level,
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    };
+
+    if visible == config.tabs.len() {
+        return buttons(0..visible, 2);
+    }
+
+    format!(
+        "{}\n{}<div class=\"{}\">\n{}<button class=\"{} {}{}\" type=\"button\" data-tabmore aria-haspopup=\"true\" aria-expanded=\"false\" aria-controls=\"{}\">More<span aria-hidden=\"true\"> ▾</span></button>\n{}<div class=\"{}\" id=\"{}\" data-tabmenu hidden>\n{}\n{}</div>\n{}</div>",
+        buttons(0..visible, 2),
+        indent(preprocessor_config, 2),
+        preprocessor_config.class("tabs-more"),
+        indent(preprocessor_config, 3),
+        preprocessor_config.class("tab"),
+        preprocessor_config.class("tabs-more-button"),
+        match active_index >= visible {
+            true => format!(" {}", preprocessor_config.active_class),
+            false => "".to_string(),
+        },
+        tabs_more_id(group),
+        indent(preprocessor_config, 3),
+        preprocessor_config.class("tabs-more-menu"),
+        tabs_more_id(group),
+        buttons(visible..config.tabs.len(), 4),
+        indent(preprocessor_config, 3),
+        indent(preprocessor_config, 2)
+    )
+}
+
+// The badge repeats the label of the selected tab next to long tab contents, and is updated by the
+// JS. It is hidden from assistive technologies, since the tablist already has the selected tab.
+fn badge(
+    config: &TabsConfig,
+    preprocessor_config: &PreprocessorConfig,
+    active_index: usize,
+) -> String {
+    match preprocessor_config.badge {
+        true => format!(
+            "{}<div class=\"{}\" data-tabbadge aria-hidden=\"true\">{}</div>\n",
+            indent(preprocessor_config, 1),
+            preprocessor_config.class("tabs-badge"),
+            escape(&plain_text(&config.tabs[active_index].0.name))
+        ),
+        false => "".to_string(),
+    }
+}
+
+// Pre-rendered contents are part of the HTML block of the tabs, which ends at a blank line. Newlines
+// before blank lines are replaced with `&#10;`, which is whitespace outside of `<pre>` elements and a
+// newline in them, so the rendered content is unchanged.
+fn pre_render(content: &str) -> String {
+    let mut html = String::new();
+    push_html(&mut html, Parser::new_ext(content, options()));
+
+    let mut rendered = String::with_capacity(html.len());
+    for (index, line) in html.trim_end().split('\n').enumerate() {
+        if index > 0 {
+            rendered.push_str(match line.trim().is_empty() {
+                true => "&#10;",
+                false => "\n",
+            });
+        }
+        rendered.push_str(line);
+    }
+
+    rendered
+}
+
+fn tab_content(
+    tab: &TabConfig,
+    tab_content: &str,
+    preprocessor_config: &PreprocessorConfig,
+    group: &str,
+    index: usize,
+    slug: &str,
+    active: bool,
+) -> String {
+    let content = match &preprocessor_config.empty_placeholder {
+        Some(placeholder) if tab_content.trim().is_empty() => format!(
+            "<p class=\"{}\">{}</p>",
+            preprocessor_config.class("tab-empty"),
+            escape(placeholder)
+        ),
+        _ => tab_content.to_string(),
+    };
+    let content = match preprocessor_config.render_mode {
+        RenderMode::Markdown => format!("\n{}\n", content),
+        RenderMode::Html => pre_render(&content),
+    };
+
+    // Without JS, tab contents are `<details>` elements with the tab label as summary. The JS
+    // replaces them with tab panels, so the content is not duplicated.
+    if preprocessor_config.no_js_fallback {
+        return format!(
+            "{}<details class=\"{}{}\" data-tabname=\"{}\" data-tab-label=\"{}\" id=\"{}\"{}{}>\n{}<summary class=\"{}\">{}{}</summary>\n{}\n{}</details>",
+            indent(preprocessor_config, 1),
+            preprocessor_config.class("tab-content"),
+            match active {
+                true => format!(" {}", preprocessor_config.active_class),
+                false => "".to_string(),
+            },
+            escape(&tab.name),
+            escape(&tab.name),
+            tab_content_id(group, slug),
+            order(preprocessor_config, 2 * index + 1),
+            match active {
+                true => " open",
+                false => "",
+            },
+            indent(preprocessor_config, 1),
+            preprocessor_config.class("tab-summary"),
+            icon(tab, preprocessor_config),
+            label(&tab.name),
+            content,
+            indent(preprocessor_config, 1)
+        );
+    }
+
+    let panel = format!(
+        "{}<div class=\"{}{}\" data-tabname=\"{}\" data-tab-label=\"{}\" id=\"{}\" role=\"tabpanel\" aria-labelledby=\"{}\"{}>\n{}\n{}</div>",
+        indent(preprocessor_config, 1),
+        preprocessor_config.class("tab-content"),
+        match active {
+            true => format!(" {}", preprocessor_config.active_class),
+            false if preprocessor_config.hidden_class.is_empty() => "".to_string(),
+            false => format!(" {}", preprocessor_config.hidden_class),
+        },
+        escape(&tab.name),
+        escape(&tab.name),
+        tab_content_id(group, slug),
+        tab_id(group, slug),
+        order(preprocessor_config, 2 * index + 1),
+        content,
+        indent(preprocessor_config, 1)
+    );
+
+    // Hidden tab contents are instantiated from the template by the JS when the tab is selected.
+    match preprocessor_config.lazy && !active {
+        true => format!(
+            "{}<template data-tabcontent=\"{}\">\n{}\n{}</template>",
+            indent(preprocessor_config, 1),
+            tab_content_id(group, slug),
+            panel,
+            indent(preprocessor_config, 1)
+        ),
+        false => panel,
+    }
+}
+
+// Raw tab content is shown as written, so it is escaped and not parsed as markdown.
+pub fn raw(content: &str, preprocessor_config: &PreprocessorConfig) -> String {
+    let content = content.trim_matches(|c| c == '\n' || c == '\r');
+
+    match preprocessor_config.is_html_renderer() {
+        true => format!(
+            "<pre class=\"{}\"><code>{}\n</code></pre>\n",
+            preprocessor_config.class("tab-raw"),
+            escape(content)
+        ),
+        false => {
+            let fence = "`".repeat(
+                content
+                    .split(|c| c != '`')
+                    .map(str::len)
+                    .max()
+                    .unwrap_or_default()
+                    .max(2)
+                    + 1,
+            );
+            format!("{}text\n{}\n{}\n", fence, content, fence)
+        }
+    }
+}
+
+pub fn tabs(config: &TabsConfig, preprocessor_config: &PreprocessorConfig, group: &str) -> String {
+    let active_index = active_index(config);
+    let slugs = tab_slugs(config);
+    // Tab names are encoded as a JSON array for the remember and sync keys, so names containing
+    // commas do not create the same key as a different set of names.
+    let names = escape(
+        &serde_json::to_string(
+            &config
+                .tabs
+                .iter()
+                .map(|(tab, _)| tab.name.as_str())
+                .collect::<Vec<_>>(),
+        )
+        .unwrap_or_default(),
+    );
 
-pub fn tabs(config: &TabsConfig) -> String {
     format!(
-        "<div class=\"mdbook-tabs-container\"{}>\n<nav class=\"mdbook-tabs\">\n{}\n</nav>\n{}\n</div>",
+        "<{} class=\"{}{}{}\"{}{}{}{}{}>\n{}{}<nav class=\"{}\" role=\"tablist\"{}{}>\n{}\n{}</nav>\n{}<div class=\"{}\" data-tabpanels>\n{}{}\n{}</div>\n</{}>",
+        preprocessor_config.wrapper_tag,
+        preprocessor_config.class("tabs-container"),
+        match preprocessor_config.orientation {
+            Orientation::Horizontal => "".to_string(),
+            Orientation::Vertical => format!(" {}", preprocessor_config.class("tabs-vertical")),
+        },
+        preprocessor_config
+            .wrapper_classes
+            .iter()
+            .map(|class| format!(" {}", class))
+            .collect::<String>(),
         config
             .global
             .as_ref()
-            .map(|global| format!(" data-tabglobal=\"{}\"", global))
+            .map(|global| format!(" data-tabglobal=\"{}\"", escape(global)))
             .unwrap_or("".to_string()),
+        match preprocessor_config.remember {
+            true => format!(" data-tabremember=\"{}\"", names),
+            false => "".to_string(),
+        },
+        match preprocessor_config.sync {
+            true => format!(" data-tabsync=\"{}\"", names),
+            false => "".to_string(),
+        },
+        match preprocessor_config.update_hash {
+            true => " data-tabhash",
+            false => "",
+        },
+        match preprocessor_config.no_js_fallback {
+            true => " data-tabfallback",
+            false => "",
+        },
+        title(config, preprocessor_config, group),
+        indent(preprocessor_config, 1),
+        preprocessor_config.class("tabs"),
+        match config.title {
+            Some(_) => format!(" aria-labelledby=\"{}\"", tabs_title_id(group)),
+            None => "".to_string(),
+        },
+        match preprocessor_config.orientation {
+            Orientation::Horizontal => "",
+            Orientation::Vertical => " aria-orientation=\"vertical\"",
+        },
+        tab_buttons(config, preprocessor_config, group, &slugs, active_index),
+        indent(preprocessor_config, 1),
+        indent(preprocessor_config, 1),
+        preprocessor_config.class("tab-panels"),
+        badge(config, preprocessor_config, active_index),
         config
             .tabs
             .iter()
             .enumerate()
-            .map(|(index, (tab, _))| format!(
-                "<button class=\"mdbook-tab{}\" data-tabname=\"{}\">{}</button>",
-                match index == 0 {
-                    true => " active",
-                    false => ""
-                },
-                tab.name,
-                tab.name
+            .map(|(index, (config, content))| tab_content(
+                config,
+                content,
+                preprocessor_config,
+                group,
+                index,
+                &slugs[index],
+                index == active_index
             ))
             .collect::<Vec<_>>()
             .join("\n"),
-        config
-            .tabs
-            .iter()
-            .enumerate()
-            .map(|(index, (tab, tab_content))| format!(
-                "<div class=\"mdbook-tab-content{}\" data-tabname=\"{}\">\n\n{}\n\n</div>",
-                match index == 0 {
-                    true => "",
-                    false => " hidden"
-                },
-                tab.name,
-                tab_content
-            ))
-            .collect::<Vec<_>>()
-            .join("\n")
+        indent(preprocessor_config, 1),
+        preprocessor_config.wrapper_tag
     )
 }
+
+pub fn sections(config: &TabsConfig) -> String {
+    let sections = config
+        .tabs
+        .iter()
+        .map(|(tab, content)| format!("### {}\n\n{}\n", tab.name, content.trim_matches('\n')))
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    match &config.title {
+        Some(title) => format!("{}\n\n{}", title, sections),
+        None => sections,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use test_log::test;
+
+    use super::*;
+
+    fn tabs_config(names: &[&str]) -> TabsConfig {
+        TabsConfig {
+            tabs: names
+                .iter()
+                .map(|name| {
+                    (
+                        TabConfig {
+                            name: name.to_string(),
+                            ..Default::default()
+                        },
+                        format!("Content of {}.", name),
+                    )
+                })
+                .collect(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_slug() {
+        assert_eq!("linux", slug("Linux"));
+        assert_eq!("windows-wsl", slug("Windows (WSL)"));
+        assert_eq!("c-c", slug("  C / C++ "));
+        assert_eq!("", slug("+++"));
+    }
+
+    #[test]
+    fn test_order_tabs() {
+        let mut config = TabsConfig {
+            order: Some("Windows, Linux,FreeBSD".to_string()),
+            ..tabs_config(&["Linux", "macOS", "Windows", "Android"])
+        };
+
+        let unknown = order_tabs(&mut config);
+
+        assert_eq!(
+            vec!["Windows", "Linux", "macOS", "Android"],
+            config
+                .tabs
+                .iter()
+                .map(|(tab, _)| tab.name.as_str())
+                .collect::<Vec<_>>()
+        );
+        assert_eq!("Content of Windows.", config.tabs[0].1);
+        assert_eq!(vec!["FreeBSD"], unknown);
+    }
+
+    #[test]
+    fn test_tab_slugs() {
+        assert_eq!(
+            vec!["example", "example-1", "other", "example-3"],
+            tab_slugs(&tabs_config(&["Example", "Example", "Other", "example"]))
+        );
+        assert_eq!(
+            vec!["a", "a-2", "a-3"],
+            tab_slugs(&tabs_config(&["a", "a-2", "a"]))
+        );
+        assert_eq!(
+            vec!["a", "a-1", "a-1-2"],
+            tab_slugs(&tabs_config(&["a", "a", "a-1"]))
+        );
+    }
+
+    #[test]
+    fn test_tabs_remember_sync_names() {
+        let preprocessor_config = PreprocessorConfig {
+            remember: true,
+            sync: true,
+            ..Default::default()
+        };
+
+        let actual = tabs(&tabs_config(&["a,b", "c"]), &preprocessor_config, "0");
+        let other = tabs(&tabs_config(&["a", "b,c"]), &preprocessor_config, "0");
+
+        for attribute in ["data-tabremember", "data-tabsync"] {
+            assert!(actual.contains(&format!(
+                "{}=\"[&quot;a,b&quot;,&quot;c&quot;]\"",
+                attribute
+            )));
+            assert!(other.contains(&format!(
+                "{}=\"[&quot;a&quot;,&quot;b,c&quot;]\"",
+                attribute
+            )));
+        }
+    }
+
+    #[test]
+    fn test_tabs_duplicate_names() {
+        let actual = tabs(
+            &tabs_config(&["Example", "Example"]),
+            &PreprocessorConfig::default(),
+            "0",
+        );
+
+        for id in ["mdbook-tab-0-example", "mdbook-tab-0-example-1"] {
+            assert_eq!(1, actual.matches(&format!("id=\"{}\"", id)).count());
+            assert_eq!(
+                1,
+                actual
+                    .matches(&format!("aria-labelledby=\"{}\"", id))
+                    .count()
+            );
+        }
+        for id in [
+            "mdbook-tab-content-0-example",
+            "mdbook-tab-content-0-example-1",
+        ] {
+            assert_eq!(1, actual.matches(&format!("id=\"{}\"", id)).count());
+            assert_eq!(
+                1,
+                actual.matches(&format!("aria-controls=\"{}\"", id)).count()
+            );
+        }
+    }
+
+    #[test]
+    fn test_tabs_aria() {
+        let actual = tabs(
+            &tabs_config(&["Linux", "macOS"]),
+            &PreprocessorConfig::default(),
+            "1",
+        );
+
+        assert_eq!(
+            "\
+            <div class=\"mdbook-tabs-container\">\n\
+            <nav class=\"mdbook-tabs\" role=\"tablist\">\n\
+            <button class=\"mdbook-tab active\" data-tabname=\"Linux\" data-tab-label=\"Linux\" id=\"mdbook-tab-1-linux\" role=\"tab\" aria-selected=\"true\" aria-controls=\"mdbook-tab-content-1-linux\" tabindex=\"0\">Linux</button>\n\
+            <button class=\"mdbook-tab\" data-tabname=\"macOS\" data-tab-label=\"macOS\" id=\"mdbook-tab-1-macos\" role=\"tab\" aria-selected=\"false\" aria-controls=\"mdbook-tab-content-1-macos\" tabindex=\"-1\">macOS</button>\n\
+            </nav>\n\
+            <div class=\"mdbook-tab-panels\" data-tabpanels>\n\
+            <div class=\"mdbook-tab-content active\" data-tabname=\"Linux\" data-tab-label=\"Linux\" id=\"mdbook-tab-content-1-linux\" role=\"tabpanel\" aria-labelledby=\"mdbook-tab-1-linux\">\n\
+            \n\
+            Content of Linux.\n\
+            \n\
+            </div>\n\
+            <div class=\"mdbook-tab-content hidden\" data-tabname=\"macOS\" data-tab-label=\"macOS\" id=\"mdbook-tab-content-1-macos\" role=\"tabpanel\" aria-labelledby=\"mdbook-tab-1-macos\">\n\
+            \n\
+            Content of macOS.\n\
+            \n\
+            </div>\n\
+            </div>\n\
+            </div>",
+            actual
+        );
+    }
+
+    #[test]
+    fn test_tabs_title() {
+        let untitled = tabs(
+            &tabs_config(&["Linux", "macOS"]),
+            &PreprocessorConfig::default(),
+            "0",
+        );
+        assert!(!untitled.contains("mdbook-tabs-title"));
+        assert!(untitled.contains("<nav class=\"mdbook-tabs\" role=\"tablist\">"));
+
+        let titled = tabs(
+            &TabsConfig {
+                title: Some("Choose <your> OS".to_string()),
+                ..tabs_config(&["Linux", "macOS"])
+            },
+            &PreprocessorConfig::default(),
+            "0",
+        );
+        assert!(titled.starts_with(
+            "\
+            <div class=\"mdbook-tabs-container\">\n\
+            <div class=\"mdbook-tabs-title\" id=\"mdbook-tabs-title-0\">Choose &lt;your&gt; OS</div>\n\
+            <nav class=\"mdbook-tabs\" role=\"tablist\" aria-labelledby=\"mdbook-tabs-title-0\">\n"
+        ));
+    }
+
+    #[test]
+    fn test_sections() {
+        assert_eq!(
+            "\
+            ### Linux\n\
+            \n\
+            Content of Linux.\n\
+            \n\
+            ### macOS\n\
+            \n\
+            Content of macOS.\n",
+            sections(&tabs_config(&["Linux", "macOS"]))
+        );
+    }
+
+    #[test]
+    fn test_tabs_label_markdown() {
+        let actual = tabs(
+            &tabs_config(&["`cargo`", "Rust & **Cargo**"]),
+            &PreprocessorConfig::default(),
+            "0",
+        );
+
+        assert!(actual.contains(
+            "<button class=\"mdbook-tab active\" data-tabname=\"`cargo`\" data-tab-label=\"`cargo`\" id=\"mdbook-tab-0-cargo\" role=\"tab\" aria-selected=\"true\" aria-controls=\"mdbook-tab-content-0-cargo\" tabindex=\"0\"><code>cargo</code></button>"
+        ));
+        assert!(actual.contains(
+            "<button class=\"mdbook-tab\" data-tabname=\"Rust &amp; **Cargo**\" data-tab-label=\"Rust &amp; **Cargo**\" id=\"mdbook-tab-0-rust-cargo\" role=\"tab\" aria-selected=\"false\" aria-controls=\"mdbook-tab-content-0-rust-cargo\" tabindex=\"-1\">Rust &amp; <strong>Cargo</strong></button>"
+        ));
+        assert!(actual.contains(
+            "<div class=\"mdbook-tab-content hidden\" data-tabname=\"Rust &amp; **Cargo**\" data-tab-label=\"Rust &amp; **Cargo**\" id=\"mdbook-tab-content-0-rust-cargo\""
+        ));
+    }
+
+    #[test]
+    fn test_tabs_icon() {
+        let mut config = tabs_config(&["GNU & Linux", "macOS"]);
+        config.tabs[0].0.icon = Some("linux".to_string());
+
+        let actual = tabs(&config, &PreprocessorConfig::default(), "0");
+
+        assert!(actual.contains(
+            "tabindex=\"0\"><span class=\"mdbook-tab-icon mdbook-tab-icon-linux\" aria-hidden=\"true\"></span>GNU &amp; Linux</button>"
+        ));
+        assert!(actual.contains("tabindex=\"-1\">macOS</button>"));
+    }
+
+    #[test]
+    fn test_tabs_class_prefix() {
+        let actual = tabs(
+            &tabs_config(&["Linux", "macOS"]),
+            &PreprocessorConfig {
+                class_prefix: "custom-".to_string(),
+                ..Default::default()
+            },
+            "0",
+        );
+
+        assert!(actual.starts_with("<div class=\"custom-tabs-container\">\n<nav class=\"custom-tabs\" role=\"tablist\">\n<button class=\"custom-tab active\""));
+        assert!(actual.contains("<div class=\"custom-tab-content hidden\""));
+        assert!(!actual.contains("class=\"mdbook-"));
+    }
+
+    #[test]
+    fn test_tabs_accordion() {
+        let actual = tabs(
+            &tabs_config(&["Linux", "macOS"]),
+            &PreprocessorConfig {
+                responsive: Some(Responsive::Accordion),
+                ..Default::default()
+            },
+            "0",
+        );
+
+        assert!(actual.contains("id=\"mdbook-tab-0-linux\" role=\"tab\" aria-selected=\"true\" aria-controls=\"mdbook-tab-content-0-linux\" tabindex=\"0\" style=\"order: 0\">"));
+        assert!(actual.contains("aria-labelledby=\"mdbook-tab-0-linux\" style=\"order: 1\">"));
+        assert!(actual.contains("tabindex=\"-1\" style=\"order: 2\">"));
+        assert!(actual.contains("aria-labelledby=\"mdbook-tab-0-macos\" style=\"order: 3\">"));
+    }
+
+    #[test]
+    fn test_tabs_lazy() {
+        let actual = tabs(
+            &tabs_config(&["Linux", "macOS"]),
+            &PreprocessorConfig {
+                lazy: true,
+                ..Default::default()
+            },
+            "0",
+        );
+
+        assert!(actual.contains(
+            "</nav>\n<div class=\"mdbook-tab-panels\" data-tabpanels>\n<div class=\"mdbook-tab-content active\" data-tabname=\"Linux\""
+        ));
+        assert!(actual.contains(
+            "</div>\n<template data-tabcontent=\"mdbook-tab-content-0-macos\">\n<div class=\"mdbook-tab-content hidden\" data-tabname=\"macOS\""
+        ));
+        assert!(actual.ends_with("Content of macOS.\n\n</div>\n</template>\n</div>\n</div>"));
+        assert_eq!(1, actual.matches("<template").count());
+    }
+
+    #[test]
+    fn test_tabs_no_js_fallback() {
+        let actual = tabs(
+            &tabs_config(&["Linux", "macOS"]),
+            &PreprocessorConfig {
+                no_js_fallback: true,
+                ..Default::default()
+            },
+            "0",
+        );
+
+        assert_eq!(
+            "\
+            <div class=\"mdbook-tabs-container\" data-tabfallback>\n\
+            <nav class=\"mdbook-tabs\" role=\"tablist\">\n\
+            <button class=\"mdbook-tab active\" data-tabname=\"Linux\" data-tab-label=\"Linux\" id=\"mdbook-tab-0-linux\" role=\"tab\" aria-selected=\"true\" aria-controls=\"mdbook-tab-content-0-linux\" tabindex=\"0\">Linux</button>\n\
+            <button class=\"mdbook-tab\" data-tabname=\"macOS\" data-tab-label=\"macOS\" id=\"mdbook-tab-0-macos\" role=\"tab\" aria-selected=\"false\" aria-controls=\"mdbook-tab-content-0-macos\" tabindex=\"-1\">macOS</button>\n\
+            </nav>\n\
+            <div class=\"mdbook-tab-panels\" data-tabpanels>\n\
+            <details class=\"mdbook-tab-content active\" data-tabname=\"Linux\" data-tab-label=\"Linux\" id=\"mdbook-tab-content-0-linux\" open>\n\
+            <summary class=\"mdbook-tab-summary\">Linux</summary>\n\
+            \n\
+            Content of Linux.\n\
+            \n\
+            </details>\n\
+            <details class=\"mdbook-tab-content\" data-tabname=\"macOS\" data-tab-label=\"macOS\" id=\"mdbook-tab-content-0-macos\">\n\
+            <summary class=\"mdbook-tab-summary\">macOS</summary>\n\
+            \n\
+            Content of macOS.\n\
+            \n\
+            </details>\n\
+            </div>\n\
+            </div>",
+            actual
+        );
+        assert_eq!(1, actual.matches("Content of macOS.").count());
+    }
+
+    #[test]
+    fn test_tabs_orientation() {
+        let horizontal = tabs(
+            &tabs_config(&["Linux", "macOS"]),
+            &PreprocessorConfig::default(),
+            "0",
+        );
+        assert!(horizontal.starts_with(
+            "<div class=\"mdbook-tabs-container\">\n<nav class=\"mdbook-tabs\" role=\"tablist\">\n"
+        ));
+        assert!(!horizontal.contains("aria-orientation"));
+
+        let vertical = tabs(
+            &tabs_config(&["Linux", "macOS"]),
+            &PreprocessorConfig {
+                orientation: Orientation::Vertical,
+                ..Default::default()
+            },
+            "0",
+        );
+        assert!(vertical.starts_with(
+            "<div class=\"mdbook-tabs-container mdbook-tabs-vertical\">\n<nav class=\"mdbook-tabs\" role=\"tablist\" aria-orientation=\"vertical\">\n"
+        ));
+    }
+
+    #[test]
+    fn test_tabs_max_visible_tabs() {
+        let config = TabsConfig {
+            default: Some("FreeBSD".to_string()),
+            ..tabs_config(&["Linux", "macOS", "Windows", "FreeBSD"])
+        };
+
+        let all = tabs(&config, &PreprocessorConfig::default(), "0");
+        assert!(!all.contains("data-tabmore"));
+        assert_eq!(
+            all,
+            tabs(
+                &config,
+                &PreprocessorConfig {
+                    max_visible_tabs: Some(4),
+                    ..Default::default()
+                },
+                "0"
+            )
+        );
+
+        let actual = tabs(
+            &config,
+            &PreprocessorConfig {
+                max_visible_tabs: Some(2),
+                pretty: true,
+                ..Default::default()
+            },
+            "0",
+        );
+        let (visible, more) = actual
+            .split_once("<div class=\"mdbook-tabs-more\">")
+            .unwrap_or_default();
+        assert!(visible.contains("id=\"mdbook-tab-0-linux\""));
+        assert!(visible.contains("id=\"mdbook-tab-0-macos\""));
+        assert!(more.starts_with(
+            "\n      <button class=\"mdbook-tab mdbook-tabs-more-button active\" type=\"button\" data-tabmore aria-haspopup=\"true\" aria-expanded=\"false\" aria-controls=\"mdbook-tabs-more-0\">More<span aria-hidden=\"true\"> ▾</span></button>\n      <div class=\"mdbook-tabs-more-menu\" id=\"mdbook-tabs-more-0\" data-tabmenu hidden>\n        <button class=\"mdbook-tab\" data-tabname=\"Windows\""
+        ));
+        assert!(more.contains(
+            "<button class=\"mdbook-tab active\" data-tabname=\"FreeBSD\" data-tab-label=\"FreeBSD\" id=\"mdbook-tab-0-freebsd\" role=\"tab\" aria-selected=\"true\""
+        ));
+        assert!(more.contains("</button>\n      </div>\n    </div>\n  </nav>\n"));
+        assert_eq!(4, actual.matches("role=\"tab\"").count());
+        assert_eq!(4, actual.matches("role=\"tabpanel\"").count());
+    }
+
+    #[test]
+    fn test_tabs_badge() {
+        let config = TabsConfig {
+            default: Some("`cargo` & Rust".to_string()),
+            ..tabs_config(&["Linux", "`cargo` & Rust"])
+        };
+
+        assert!(!tabs(&config, &PreprocessorConfig::default(), "0").contains("data-tabbadge"));
+
+        let actual = tabs(
+            &config,
+            &PreprocessorConfig {
+                badge: true,
+                ..Default::default()
+            },
+            "0",
+        );
+        assert!(actual.contains(
+            "<div class=\"mdbook-tab-panels\" data-tabpanels>\n<div class=\"mdbook-tabs-badge\" data-tabbadge aria-hidden=\"true\">cargo &amp; Rust</div>\n<div class=\"mdbook-tab-content hidden\" data-tabname=\"Linux\""
+        ));
+        assert_eq!(1, actual.matches("data-tabbadge").count());
+    }
+
+    #[test]
+    fn test_tabs_wrapper() {
+        let actual = tabs(
+            &tabs_config(&["Linux", "macOS"]),
+            &PreprocessorConfig {
+                wrapper_tag: "section".to_string(),
+                wrapper_classes: vec!["not-prose".to_string(), "my-4".to_string()],
+                orientation: Orientation::Vertical,
+                ..Default::default()
+            },
+            "0",
+        );
+
+        assert!(actual.starts_with(
+            "<section class=\"mdbook-tabs-container mdbook-tabs-vertical not-prose my-4\">\n"
+        ));
+        assert!(actual.ends_with("</div>\n</section>"));
+        assert!(tabs(
+            &tabs_config(&["Linux", "macOS"]),
+            &PreprocessorConfig::default(),
+            "0"
+        )
+        .ends_with("</div>\n</div>"));
+    }
+
+    #[test]
+    fn test_tabs_render_mode() {
+        let mut config = tabs_config(&["Linux", "macOS"]);
+        config.tabs[0].1 =
+            "Install with **apt**:\n\n```shell\napt update\n\n  \napt install example\n```\n"
+                .to_string();
+
+        let markdown = tabs(&config, &PreprocessorConfig::default(), "0");
+        assert!(
+            markdown.contains("aria-labelledby=\"mdbook-tab-0-linux\">\n\nInstall with **apt**:\n")
+        );
+        assert!(markdown
+            .contains("aria-labelledby=\"mdbook-tab-0-macos\">\n\nContent of macOS.\n\n</div>"));
+
+        for preprocessor_config in [
+            PreprocessorConfig {
+                render_mode: RenderMode::Html,
+                ..Default::default()
+            },
+            PreprocessorConfig {
+                render_mode: RenderMode::Html,
+                no_js_fallback: true,
+                ..Default::default()
+            },
+        ] {
+            let html = tabs(&config, &preprocessor_config, "0");
+            assert!(html.contains(
+                "<p>Install with <strong>apt</strong>:</p>\n\
+                <pre><code class=\"language-shell\">apt update&#10;&#10;  \napt install example\n</code></pre>\n"
+            ));
+            assert!(html.contains(">\n<p>Content of macOS.</p>\n</"));
+
+            // The whole tabs are one HTML block, so the renderer does not parse the contents.
+            assert!(Parser::new(&html).all(|event| matches!(
+                event,
+                Event::Start(Tag::HtmlBlock) | Event::End(TagEnd::HtmlBlock) | Event::Html(_)
+            )));
+        }
+    }
+
+    #[test]
+    fn test_tabs_classes() {
+        let actual = tabs(
+            &tabs_config(&["Linux", "macOS"]),
+            &PreprocessorConfig {
+                active_class: "is-active".to_string(),
+                hidden_class: "".to_string(),
+                ..Default::default()
+            },
+            "0",
+        );
+
+        assert!(actual.contains("<button class=\"mdbook-tab is-active\" data-tabname=\"Linux\""));
+        assert!(
+            actual.contains("<div class=\"mdbook-tab-content is-active\" data-tabname=\"Linux\"")
+        );
+        assert!(actual.contains("<div class=\"mdbook-tab-content\" data-tabname=\"macOS\""));
+        assert!(!actual.contains("hidden"));
+    }
+
+    #[test]
+    fn test_tabs_pretty() {
+        let actual = tabs(
+            &tabs_config(&["Linux", "macOS"]),
+            &PreprocessorConfig {
+                pretty: true,
+                ..Default::default()
+            },
+            "0",
+        );
+
+        let lines = actual.lines().collect::<Vec<_>>();
+        assert_eq!("<div class=\"mdbook-tabs-container\">", lines[0]);
+        assert_eq!("  <nav class=\"mdbook-tabs\" role=\"tablist\">", lines[1]);
+        assert!(lines[2].starts_with("    <button class=\"mdbook-tab active\""));
+        assert!(lines[3].starts_with("    <button class=\"mdbook-tab\""));
+        assert_eq!("  </nav>", lines[4]);
+        assert_eq!(
+            "  <div class=\"mdbook-tab-panels\" data-tabpanels>",
+            lines[5]
+        );
+        assert!(lines[6].starts_with("  <div class=\"mdbook-tab-content active\""));
+        assert_eq!(
+            vec!["", "Content of Linux.", "", "  </div>"],
+            lines[7..11].to_vec()
+        );
+        assert_eq!(
+            vec!["  </div>", "</div>"],
+            lines[lines.len() - 2..].to_vec()
+        );
+    }
+}
@@ -0,0 +1,77 @@
+use crate::config::{PreprocessorConfig, Responsive};
+
+const CSS: &str = include_str!("theme/tabs.css");
+const ACCORDION_CSS: &str = include_str!("theme/tabs-accordion.css");
+const JS: &str = include_str!("theme/tabs.js");
+
+pub fn css(config: &PreprocessorConfig) -> String {
+    let mut css = CSS.to_string();
+
+    if config.responsive == Some(Responsive::Accordion) {
+        css.push_str(&format!(
+            "\n@media (max-width: {}px) {{\n{}}}\n",
+            config.breakpoint,
+            ACCORDION_CSS
+                .lines()
+                .map(|line| match line.is_empty() {
+                    true => "\n".to_string(),
+                    false => format!("    {}\n", line),
+                })
+                .collect::<String>()
+        ));
+    }
+
+    css.replace(".mdbook-", &format!(".{}", config.class_prefix))
+        .replace(".active", &format!(".{}", config.active_class))
+}
+
+pub fn js(config: &PreprocessorConfig) -> String {
+    JS.replacen(
+        "const ACTIVE_CLASS = 'active';",
+        &format!("const ACTIVE_CLASS = '{}';", config.active_class),
+        1,
+    )
+    .replacen(
+        "const HIDDEN_CLASS = 'hidden';",
+        &format!("const HIDDEN_CLASS = '{}';", config.hidden_class),
+        1,
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use test_log::test;
+
+    use super::*;
+
+    #[test]
+    fn test_css_accordion() {
+        assert!(!css(&PreprocessorConfig::default()).contains("@media"));
+
+        let actual = css(&PreprocessorConfig {
+            responsive: Some(Responsive::Accordion),
+            breakpoint: 500,
+            class_prefix: "custom-".to_string(),
+            ..Default::default()
+        });
+
+        assert!(actual.contains("\n@media (max-width: 500px) {\n    .custom-tabs-container {\n"));
+        assert!(actual.ends_with("}\n"));
+        assert!(!actual.contains(".mdbook-"));
+    }
+
+    #[test]
+    fn test_classes() {
+        let config = PreprocessorConfig {
+            active_class: "is-active".to_string(),
+            hidden_class: "".to_string(),
+            ..Default::default()
+        };
+
+        assert!(css(&config).contains(".mdbook-tab.is-active {"));
+        assert!(!css(&config).contains(".active"));
+        assert!(js(&config).contains("const ACTIVE_CLASS = 'is-active';"));
+        assert!(js(&config).contains("const HIDDEN_CLASS = '';"));
+        assert_eq!(JS, js(&PreprocessorConfig::default()));
+    }
+}
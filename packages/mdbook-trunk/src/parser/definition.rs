@@ -18,7 +18,7 @@ pub fn parse_definitions(chapter: &Chapter) -> Result<Vec<(Range<usize>, Config)
     debug!("{:?}", blocks);
 
     for block in blocks {
-        let config = Config::parse_from_toml(&chapter.content[block.inner_span.clone()])?;
+        let config = Config::parse_from_toml(block.content(&chapter.content)?)?;
         configs.push((block.span, config));
     }
 
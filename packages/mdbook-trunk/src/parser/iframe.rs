@@ -28,7 +28,7 @@ pub fn parse_iframes(chapter: &Chapter) -> Result<Vec<(Range<usize>, Config)>> {
     debug!("{:?}", blocks);
 
     for block in blocks {
-        let dom = Dom::parse(&chapter.content[block.span.clone()])?;
+        let dom = Dom::parse(block.outer(&chapter.content)?)?;
         let element = dom
             .children
             .iter()